@@ -1,6 +1,9 @@
-use super::state::{State, CursorMovement, ChatMessage, MessageType, ScrollMovement};
-use crate::cardascii::common::{Card, HandCardData};
-use crate::cardascii::terminal::{draw_hand_from_array, draw_hand_from_stack};
+use super::state::{State, CursorMovement, ChatMessage, MessageType, ScrollMovement, SearchMovement};
+use crate::cardascii::common::{Card, HandCardData, TurnOutcome, TurnRecord};
+use crate::cardascii::terminal::{draw_hand_from_slice, set_ascii_cards};
+use crate::cardascii::builder::Piece;
+use crate::cardascii::answer::{explain, parse_answer, AnswerExplanation, Expr};
+use crate::cardascii::art::CardPack;
 use crate::state::Window;
 use crate::renderer::{Renderer};
 use crate::action::{Action, Processing};
@@ -8,15 +11,22 @@ use crate::commands::{CommandManager};
 use crate::message::{NetMessage, Chunk};
 use crate::util::{Error, Result, Reportable};
 use crate::commands::send_file::{SendFileCommand};
-use crate::commands::cardascii_answer::{CardasciiAnswerCommand};
+use crate::commands::cardascii_answer::{CardasciiAnswerCommand, CardasciiAnswer};
+use crate::commands::direct_message::{DirectMessageCommand, ReplyCommand};
+use crate::commands::history::{HistoryCommand};
+use crate::commands::export::{ExportCommand};
+use crate::commands::filter::{FilterCommand};
+use crate::commands::bot::{BotCommand};
+use crate::commands::solo::{SkipCommand, RevealCommand, StatsCommand};
+use crate::history::{History, HistoryKind};
 #[cfg(feature = "stream-video")]
 use crate::commands::send_stream::{SendStreamCommand, StopStreamCommand};
 use crate::config::{Config, NodeType};
 use crate::encoder::{self, Encoder};
 
 use crossterm::event::{Event as TermEvent, KeyCode, KeyEvent, KeyModifiers};
+use chrono::TimeZone;
 
-use std::convert::TryInto;
 
 use message_io::network::{NetEvent, Endpoint, Transport};
 use message_io::node::{self, NodeEvent, NodeListener, NodeHandler};
@@ -33,10 +43,16 @@ use std::{
 use std::net::SocketAddrV4;
 use std::sync::atomic::{AtomicBool, Ordering};
 use crate::cardascii::game::{Game24, Game24Err, TurnResult};
+use crate::cardascii::solver;
+use crate::cardascii::bot::{BotMove, SOLUTIONS_LOOKED};
+use crate::cardascii::solo::{daily_seed, Solo, SoloMove, DAILY_HANDS};
+use crate::cardascii::rules::Rules;
 
 pub enum Signal {
     Terminal(TermEvent),
     Action(Box<dyn Action>),
+    // Move of a bot of the table for a turn
+    Bot(String, u32, BotMove),
     // Close event with an optional error in case of failure
     // Close(None) means no error happened
     Close(Option<Error>),
@@ -49,6 +65,9 @@ pub struct Application {
 }
 
 impl<'a> Application {
+    /// Solutions shown by `?reveal` in solo mode
+    const REVEALED_SOLUTIONS: usize = 5;
+
     pub fn new(config: Config) -> Application {
         let commands = CommandManager::default().with(SendFileCommand);
 
@@ -56,13 +75,80 @@ impl<'a> Application {
         let commands = commands.with(SendStreamCommand).with(StopStreamCommand);
 
         let commands = commands.with(CardasciiAnswerCommand).with(CardasciiPassCommand);
+        let commands = commands.with(DirectMessageCommand).with(ReplyCommand);
+        let commands = commands.with(HistoryCommand).with(ExportCommand).with(FilterCommand);
+        let commands = commands.with(BotCommand);
+        let commands = commands.with(SkipCommand).with(RevealCommand).with(StatsCommand);
         let mut state = State::default();
+        state.local_user = config.user_name.clone();
+
+        // Everyone plays the same daily puzzle: same hands and same rules
+        let daily = config.daily.then(|| chrono::Local::now().date_naive());
+        let seed = match daily {
+            Some(date) => Some(daily_seed(date)),
+            None => config.seed,
+        };
 
-        state.game24 = match config.boot {
-            true => Some(Game24::new()),
+        state.game24 = match config.boot || config.is_solo() {
+            true => {
+                let mut rules = match config.rules.validate() {
+                    _ if daily.is_some() => Rules::default(),
+                    Ok(()) => config.rules.clone(),
+                    Err(e) => {
+                        format!("Invalid game rules in the config, using the default ones: {}", e)
+                            .report_err(&mut state);
+                        Rules::default()
+                    }
+                };
+                // Alone there is nobody to hide the answers from
+                if config.is_solo() {
+                    rules.sealed_answers = false;
+                }
+                match seed {
+                    Some(seed) => Some(Game24::with_seed(rules, seed)),
+                    None => Some(Game24::new(rules)),
+                }
+            }
             false => None,
         };
 
+        if config.is_solo() {
+            if let Some(game) = &mut state.game24 {
+                let _ = game.do_user_registration(&config.user_name);
+                let mode = match daily {
+                    Some(date) => format!(
+                        "Daily puzzle of {} ({} hands)",
+                        date.format("%Y-%m-%d"),
+                        DAILY_HANDS
+                    ),
+                    None => format!("Solo mode (seed {})", game.seed()),
+                };
+                let solo = Solo::new(game.seed(), daily);
+                format!("{}: {}", mode, game.rules().description()).report_info(&mut state);
+                state.set_solo(Some(solo));
+            }
+        }
+
+        set_ascii_cards(config.card_charset.is_ascii());
+        if let Some(name) = &config.card_pack {
+            load_card_pack(name, &mut state);
+        }
+
+        if config.boot {
+            match History::load(config.history_size) {
+                Ok(history) => {
+                    state.add_history(Vec::new(), history.next_id());
+                    state.history = Some(history);
+                }
+                Err(e) => format!("Unable to load the history: {}", e).report_err(&mut state),
+            }
+            // Only the table knows the seed, with it the next hands could be computed.
+            // It is kept in the local log (and `?export`) to replay the game with `--seed`.
+            if let Some(game) = &state.game24 {
+                format!("Deck seed of this game: {}", game.seed()).report_info(&mut state);
+            }
+        }
+
         Application { config, commands, state }
     }
 
@@ -144,6 +230,12 @@ impl<'a> Application {
         self.state.add_message(message);
     }
 
+    fn log_game_event(&mut self, msg: String) {
+        self.state.record_history(HistoryKind::Game, "Table", &msg);
+        let message = ChatMessage::new("Table".to_owned(), MessageType::Game(msg));
+        self.state.add_message(message);
+    }
+
     fn process_network_message(
         &mut self,
         endpoint: Endpoint,
@@ -161,11 +253,22 @@ impl<'a> Application {
                     node.network().send(endpoint, encoder.encode(message));
                     self.state.connected_user(endpoint, &user);
 
-                    if let Some(game) = & self.state.game24 {
+                    if let Some(game) = &mut self.state.game24 {
+                        // The user could already be registered from a previous connection
+                        let _ = game.do_user_registration(&user);
+                        node.network().send(endpoint, encoder.encode(
+                            NetMessage::CardasciiRules(game.rules().clone())));
+                        if let Some(hand) = get_vec_gived_cards(game) {
+                            node.network().send(endpoint, encoder.encode(
+                                NetMessage::CardasciiNewTurn(game.status(), hand, game.target_card().cloned())));
+                        }
+                    }
+
+                    if let Some(history) = &self.state.history {
+                        let (entries, oldest_id) =
+                            history.page(history.next_id(), self.config.history_replay);
                         node.network().send(endpoint, encoder.encode(
-                            NetMessage::CardasciiNewTurn(
-                                get_vec_gived_cards(game)
-                            )));
+                            NetMessage::History(entries, oldest_id)));
                     }
                 }
             }
@@ -175,12 +278,53 @@ impl<'a> Application {
                 self.righ_the_bell();
             }
             NetMessage::UserMessage(content) => {
-                if let Some(user) = self.state.user_name(&endpoint) {
-                    let message = ChatMessage::new(user.into(), MessageType::Text(content));
+                if let Some(user) = self.state.user_name(&endpoint).cloned() {
+                    self.state.record_history(HistoryKind::Chat, &user, &content);
+                    let message = ChatMessage::new(user, MessageType::Text(content));
                     self.state.add_message(message);
                     self.righ_the_bell();
                 }
             }
+            NetMessage::HistoryRequest(before, count) => {
+                if let Some(history) = &self.state.history {
+                    let (entries, oldest_id) =
+                        history.page(before.unwrap_or_else(|| history.next_id()), count);
+                    node.network().send(endpoint, encoder.encode(
+                        NetMessage::History(entries, oldest_id)));
+                }
+            }
+            NetMessage::History(entries, oldest_id) => {
+                if entries.is_empty() && self.state.oldest_history_id().is_some() {
+                    String::from("There are no older messages").report_info(&mut self.state);
+                }
+                self.state.add_history(entries, oldest_id);
+            }
+            NetMessage::DirectMessage(from_user, to_user, content) => {
+                if let Some(sender) = self.state.user_name(&endpoint).cloned() {
+                    if to_user == self.config.user_name {
+                        // Only the table relays messages, the other users can't write in the name of others
+                        let from_user = match self.state.table_endpoint() == Some(&endpoint) {
+                            true => from_user,
+                            false => sender,
+                        };
+                        self.state.add_private_message(&from_user, content);
+                        self.righ_the_bell();
+                    }
+                    else if self.state.game24.is_some() {
+                        match self.state.user_endpoint(&to_user).cloned() {
+                            Some(to_endpoint) => {
+                                // Relay: the sender and the receiver are not directly connected.
+                                let message = NetMessage::DirectMessage(sender, to_user, content);
+                                node.network().send(to_endpoint, encoder.encode(message));
+                            }
+                            None => {
+                                let notice = format!("User '{}' is not connected", to_user);
+                                node.network().send(endpoint, encoder.encode(NetMessage::UserMessage(notice)));
+                            }
+                        }
+                    }
+                }
+            }
             NetMessage::UserData(file_name, chunk) => {
                 use std::io::Write;
                 if self.state.user_name(&endpoint).is_some() {
@@ -236,97 +380,522 @@ impl<'a> Application {
                     self.state.windows.remove(&endpoint);
                 }
             },
-            NetMessage::CardasciiNewTurn(hand) => {
+            NetMessage::CardasciiRules(rules) => {
                 if self.state.game24.is_none() {
-                    self.state.cards = draw_hand_from_array(& hand);
+                    if let Some(table) = self.state.user_name(&endpoint).cloned() {
+                        let content = format!("rules of this table: {}", rules.description());
+                        self.state.add_message(ChatMessage::new(table, MessageType::Game(content)));
+                    }
+                    self.state.set_rules(rules);
+                    self.state.set_table_endpoint(endpoint);
                 }
             }
-            NetMessage::CardasciiAnswer(content) => {
-                let mut opt_user : Option<String> = None;
-                if let Some(user) = self.state.user_name(&endpoint) {
-                    opt_user = Some( user.clone() );
-                    let message = ChatMessage::new(
-                        user.into(),
-                        MessageType::Text(format!("24Game_answer! > {content}")),
-                    );
-                    self.state.add_message(message);
-                    self.righ_the_bell();
+            NetMessage::CardasciiNewTurn(status, hand, target_card) => {
+                if self.state.game24.is_none() {
+                    self.state.cards = draw_hand_from_slice(& hand);
+                    self.state.set_hand(status, hand, target_card);
                 }
-
-                if let Some (t_user) = opt_user {
-                    if self.state.game24.is_some() {
-                        //let Some(game) = &mut self.state.game24 {
-                        let message2 = ChatMessage::new(
-                            "Me".to_string(),
-                            MessageType::Text(format!("analizing > {content}")),
-                        );
-    
-                        self.state.add_message(message2);
-    
-                        let game = self.state.game24.as_mut().unwrap();
-                        let result_message = match game.make_answer( & t_user, content.clone()) {
-                            Ok( _ ) => {
-                                match game.do_give_cards() {
-                                    Ok(turn) => {
-                                        self.state.cards = draw_hand_from_stack(& turn.visible_cards);
-                                        format!("correct answer!! =_= > {}", content.clone())
-                                    }
-                                    Err(Game24Err(msg)) =>
-                                        format!("correct answer!! =_= > {}. {}", content.clone(), msg)
-                                }
-                            },
-                            Err(Game24Err(msg)) =>
-                                format!("isn't correct answer!! =_= > {} > problem: {}",content.clone(), msg),
-                        };
-
-                        let hand = get_vec_gived_cards(game);
-                        for endpoint in self.state.all_user_endpoints() {
-                            node.network().send(
-                                *endpoint,
-                                encoder.encode(
-                                    NetMessage::UserMessage(result_message.clone())
-                                ),
-                            );
-
-                            node.network().send(
-                                *endpoint,
-                                encoder.encode(
-                                    NetMessage::CardasciiNewTurn(
-                                        hand
-                                    )
-                                ),
+            }
+            NetMessage::CardasciiTurnStatus(status) => {
+                if self.state.game24.is_none() {
+                    self.state.set_turn_status(status);
+                }
+            }
+            NetMessage::CardasciiAnswer(turn, content) => {
+                if let Some(user) = self.state.user_name(&endpoint).cloned() {
+                    match self.state.game24.is_some() {
+                        true => self.process_answer(Some(endpoint), user, turn, content, node, encoder),
+                        false => {
+                            let message = ChatMessage::new(
+                                user,
+                                MessageType::Game(format!("24Game_answer! > {content}")),
                             );
+                            self.state.add_message(message);
+                            self.righ_the_bell();
                         }
                     }
                 }
-                
             },
-            NetMessage::CardasciiPass() => {
+            NetMessage::CardasciiEvent(content) => {
                 if let Some(user) = self.state.user_name(&endpoint).cloned() {
-                    if let Some(game) = self.state.game24.as_mut() {
-                        match game.do_pass(&user) {
-                            Ok(turn) => {
-                                match turn.result {
-                                    TurnResult::Tie         =>
-                                        self.log_in_chat(format!("all players passed this turn")),
-                                    TurnResult::Gaming      =>
-                                        self.log_in_chat(format!("some player passed this turn")),
-                                    TurnResult::Winner(win)   =>
-                                        self.log_in_chat(format!("have a winner!")),
-                                    TurnResult::Abandoned   =>
-                                        self.log_in_chat(format!("why!!!")),
-                                }
+                    self.state.add_message(ChatMessage::new(user, MessageType::Game(content)));
+                    self.righ_the_bell();
+                }
+            },
+            NetMessage::CardasciiRejected(user, explanation) => {
+                if let Some(table) = self.state.user_name(&endpoint).cloned() {
+                    let content = rejection_text(&user, &explanation);
+                    self.state.add_message(ChatMessage::new(table, MessageType::Game(content)));
+                    self.righ_the_bell();
+                }
+            },
+            NetMessage::CardasciiTurnResult(record) => {
+                if self.state.game24.is_none() {
+                    self.state.add_turn_result(record);
+                }
+            },
+            NetMessage::CardasciiPass(turn) => {
+                if let Some(user) = self.state.user_name(&endpoint).cloned() {
+                    self.process_pass(Some(endpoint), user, turn, node, encoder);
+                }
+            }
+        }
+    }
 
+    /// Only the table processes the passes, `endpoint` is `None` for the bots of the table
+    fn process_pass(
+        &mut self,
+        endpoint: Option<Endpoint>,
+        user: String,
+        turn: u32,
+        node: &NodeHandler<Signal>,
+        encoder: &mut Encoder,
+    ) {
+        if let Some(game) = self.state.game24.as_mut() {
+            if let Err(Game24Err(msg)) = game.check_turn(turn) {
+                if let Some(endpoint) = endpoint {
+                    self.notify(endpoint, format!("pass ignored, {msg}"), node, encoder);
+                }
+                return
+            }
+            let turn_cards = game.get_gived_cards().into_iter().cloned().collect();
+            let target_card = game.target_card().cloned();
+            match game.do_pass(&user, turn) {
+                Ok(turn) => {
+                    match turn.result {
+                        TurnResult::Tie         => {
+                            let turn = turn.num();
+                            self.end_tied_turn(turn, turn_cards, target_card, node, encoder);
+                        }
+                        TurnResult::Gaming      => {
+                            let status = game.status();
+                            let message = encoder.encode(NetMessage::CardasciiTurnStatus(status));
+                            for endpoint in self.state.all_user_endpoints() {
+                                node.network().send(*endpoint, message);
                             }
-                            Err(Game24Err(msg)) => self.log_in_chat(msg)
+                            self.announce(format!("{user} passed this turn"), node, encoder)
                         }
+                        TurnResult::Winner(_)   =>
+                            self.announce(String::from("have a winner!"), node, encoder),
+                        TurnResult::Abandoned   =>
+                            self.announce(String::from("why!!!"), node, encoder),
                     }
+
                 }
+                Err(Game24Err(msg)) => self.log_in_chat(msg)
+            }
+        }
+    }
 
+    /// Every player still at the table passed the turn
+    fn end_tied_turn(
+        &mut self,
+        turn: u32,
+        cards: Vec<Card>,
+        target_card: Option<Card>,
+        node: &NodeHandler<Signal>,
+        encoder: &mut Encoder,
+    ) {
+        let record = TurnRecord {
+            turn,
+            timestamp: chrono::Local::now().timestamp(),
+            cards,
+            target_card,
+            outcome: TurnOutcome::Tie,
+            solutions: 0,
+            solutions_complete: false,
+        };
+        self.announce(String::from("all players passed this turn"), node, encoder);
+        self.end_of_turn(record, node, encoder);
+    }
+
+    /// Only the table: the turns don't wait for the players (or bots) that left,
+    /// the turn ends if the rest of the players already passed
+    fn process_leave(&mut self, user: String, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        if let Some(game) = self.state.game24.as_mut() {
+            let turn_cards = game.get_gived_cards().into_iter().cloned().collect();
+            let target_card = game.target_card().cloned();
+            if let Ok(true) = game.do_user_leave(&user) {
+                let turn = game.turn_num();
+                self.end_tied_turn(turn, turn_cards, target_card, node, encoder);
             }
         }
     }
 
+    fn process_disconnection(&mut self, endpoint: Endpoint, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        let user = self.state.user_name(&endpoint).cloned();
+        self.state.disconnected_user(endpoint);
+        //If the endpoint was sending a stream make sure to close its window
+        self.state.windows.remove(&endpoint);
+        if let Some(user) = user {
+            self.process_leave(user, node, encoder);
+        }
+    }
+
+    /// Only the table processes the answers, `endpoint` is `None` for the bots of the table
+    fn process_answer(
+        &mut self,
+        endpoint: Option<Endpoint>,
+        user: String,
+        turn: u32,
+        content: String,
+        node: &NodeHandler<Signal>,
+        encoder: &mut Encoder,
+    ) {
+        let game = match self.state.game24.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+        // The first valid answer received ends the turn, the later ones are rejected
+        // without being announced: only their author is told.
+        if let Err(Game24Err(msg)) = game.check_turn(turn) {
+            let msg = match self.winner_with_same_solution(turn, &content) {
+                Some(winner) => format!("{msg}, {winner} found the same solution first"),
+                None => msg,
+            };
+            if let Some(endpoint) = endpoint {
+                self.notify(endpoint, format!("answer ignored > {content} > {msg}"), node, encoder);
+            }
+            return
+        }
+        let turn_cards = game.get_gived_cards().into_iter().cloned().collect();
+        let target_card = game.target_card().cloned();
+        let turn_num = game.turn_num();
+
+        match game.make_answer(&user, turn, content.clone()) {
+            Ok(solution) => {
+                let record = TurnRecord {
+                    turn: turn_num,
+                    timestamp: chrono::Local::now().timestamp(),
+                    cards: turn_cards,
+                    target_card,
+                    outcome: TurnOutcome::Winner(user.clone(), solution.to_string()),
+                    solutions: 0,
+                    solutions_complete: false,
+                };
+                let solution = solution.pretty();
+                self.announce(format!("correct answer by {user}!! =_= > {solution}"), node, encoder);
+                self.end_of_turn(record, node, encoder);
+            }
+            Err(explanation) => {
+                let message = encoder.encode(NetMessage::CardasciiRejected(user.clone(), *explanation.clone()));
+                match self.state.rules().sealed_answers {
+                    true => {
+                        // Only the author knows the result until the turn ends
+                        if let Some(endpoint) = endpoint {
+                            node.network().send(endpoint, message);
+                        }
+                        self.announce(format!("{user} made an answer"), node, encoder);
+                    }
+                    false => {
+                        for endpoint in self.state.all_user_endpoints() {
+                            node.network().send(*endpoint, message);
+                        }
+                        self.log_game_event(rejection_text(&user, &explanation));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Announces the new bots and plans the move of the bots that have no move
+    /// on the way for the current turn
+    fn schedule_bots(&mut self, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        for name in self.state.take_left_bots() {
+            self.announce(format!("{} left the table", name), node, encoder);
+            self.process_leave(name, node, encoder);
+        }
+
+        let joined = self
+            .state
+            .bots_mut()
+            .iter_mut()
+            .filter(|bot| !bot.is_announced())
+            .map(|bot| {
+                bot.set_announced();
+                format!("{} joined the table ({} bot)", bot.name, bot.skill.name())
+            })
+            .collect::<Vec<_>>();
+        for msg in joined {
+            self.announce(msg, node, encoder);
+        }
+
+        let game = match &self.state.game24 {
+            Some(game) if game.check_turn(game.turn_num()).is_ok() => game,
+            _ => return,
+        };
+        let turn = game.turn_num();
+        if self.state.bots().iter().all(|bot| bot.is_planned(turn)) {
+            return
+        }
+        let cards = game.get_gived_cards().into_iter().cloned().collect::<Vec<_>>();
+        let values = cards.iter().map(|card| card.value).collect::<Vec<_>>();
+        let rules = game.rules().clone();
+        let solutions = solver::solutions(&values, game.target(), &rules, SOLUTIONS_LOOKED);
+        for bot in self.state.bots_mut().iter_mut().filter(|bot| !bot.is_planned(turn)) {
+            let (delay, bot_move) = bot.plan(&cards, &solutions.found, &rules);
+            bot.set_planned(Some(turn));
+            node.signals().send_with_timer(Signal::Bot(bot.name.clone(), turn, bot_move), delay);
+        }
+    }
+
+    /// The moves of the bots take the same path as the ones received from the players
+    fn process_bot_move(
+        &mut self,
+        name: String,
+        turn: u32,
+        bot_move: BotMove,
+        node: &NodeHandler<Signal>,
+        encoder: &mut Encoder,
+    ) {
+        // The bot was removed while thinking
+        if self.state.bot_mut(&name).is_none() {
+            return
+        }
+        match bot_move {
+            BotMove::Answer(answer) => {
+                self.process_answer(None, name.clone(), turn, answer, node, encoder);
+                // After a wrong answer the bot keeps thinking
+                if let Some(bot) = self.state.bot_mut(&name).filter(|bot| bot.is_planned(turn)) {
+                    bot.set_planned(None);
+                }
+                self.schedule_bots(node, encoder);
+            }
+            BotMove::Pass => self.process_pass(None, name, turn, node, encoder),
+        }
+    }
+
+    /// Solo mode: the moves of the player are checked by the local game as soon as they are made
+    fn process_solo_moves(&mut self, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        let moves = match self.state.solo_mut() {
+            Some(solo) => solo.take_moves(),
+            None => return,
+        };
+        let user = self.config.user_name.clone();
+        for solo_move in moves {
+            if let Some(solo) = self.state.solo().filter(|solo| solo.is_finished()) {
+                let msg = format!("The daily puzzle is finished: {}", solo.result_line());
+                msg.report_info(&mut self.state);
+                break
+            }
+            match solo_move {
+                SoloMove::Answer(turn, answer) => {
+                    // The hand is counted before the answer ends it and the next one is dealt,
+                    // that could be after the last hand of the daily puzzle
+                    let solved = match &self.state.game24 {
+                        Some(game) if game.check_turn(turn).is_ok() => {
+                            let cards = game.get_gived_cards().into_iter().cloned().collect::<Vec<_>>();
+                            Some(explain(&answer, &cards, game.target(), game.rules()).is_correct())
+                        }
+                        _ => None,
+                    };
+                    let msg = match (self.state.solo_mut(), solved) {
+                        (Some(solo), Some(true)) => {
+                            let time = solo.hand_time();
+                            solo.solved(time);
+                            Some(format!("solved in {:.1}s • streak {}", time.as_secs_f64(), solo.streak()))
+                        }
+                        (Some(solo), Some(false)) => {
+                            solo.missed();
+                            None
+                        }
+                        _ => None,
+                    };
+                    self.process_answer(None, user.clone(), turn, answer, node, encoder);
+                    if let Some(msg) = msg {
+                        self.log_game_event(msg);
+                    }
+                }
+                SoloMove::Skip => self.end_solo_hand(false, node, encoder),
+                SoloMove::Reveal => self.end_solo_hand(true, node, encoder),
+            }
+        }
+    }
+
+    /// Solo mode: gives up the current hand and deals a new one
+    fn end_solo_hand(&mut self, reveal: bool, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        let user = self.config.user_name.clone();
+        let game = match self.state.game24.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+        let turn = game.turn_num();
+        let cards = game.get_gived_cards().into_iter().cloned().collect::<Vec<Card>>();
+        let target_card = game.target_card().cloned();
+        let values = cards.iter().map(|card| card.value).collect::<Vec<_>>();
+        let solutions = solver::solutions(&values, game.target(), game.rules(), solver::MAX_SOLUTIONS);
+        if let Err(Game24Err(msg)) = game.do_pass(&user, turn) {
+            return msg.report_err(&mut self.state)
+        }
+        let record = TurnRecord {
+            turn,
+            timestamp: chrono::Local::now().timestamp(),
+            cards,
+            target_card,
+            outcome: TurnOutcome::Tie,
+            solutions: solutions.found.len(),
+            solutions_complete: solutions.complete,
+        };
+        match reveal {
+            true => {
+                self.log_game_event(match (solutions.found.len(), solutions.complete) {
+                    (0, true) => String::from("this hand had no solution"),
+                    (0, false) => String::from("no solution was found for this hand"),
+                    _ => format!("this hand had {}:", solutions.count_text()),
+                });
+                for solution in solutions.found.iter().take(Self::REVEALED_SOLUTIONS) {
+                    self.log_game_event(solution.pretty());
+                }
+                if solutions.found.len() > Self::REVEALED_SOLUTIONS {
+                    let more = solutions.found.len() - Self::REVEALED_SOLUTIONS;
+                    self.log_game_event(format!("and {} more", more));
+                }
+            }
+            false => self.log_game_event(String::from("hand skipped")),
+        }
+        if let Some(solo) = self.state.solo_mut() {
+            match reveal {
+                true => solo.revealed(),
+                false => solo.skipped(),
+            }
+        }
+        self.state.add_turn_result(record);
+        self.start_new_turn(node, encoder);
+    }
+
+    /// Winner of an already finished turn whose solution is the same as `answer`
+    fn winner_with_same_solution(&self, turn: u32, answer: &str) -> Option<String> {
+        let record = self.state.turn_results().iter().rev().find(|record| record.turn == turn)?;
+        match &record.outcome {
+            TurnOutcome::Winner(winner, solution) => {
+                let solution = Expr::parse(solution).ok()?;
+                let late = parse_answer(answer, &record.cards, self.state.rules()).ok()?;
+                match late.is_same_solution(&solution) {
+                    true => Some(winner.clone()),
+                    false => None,
+                }
+            }
+            TurnOutcome::Tie => None,
+        }
+    }
+
+    /// Shows a game event only to one player
+    fn notify(&mut self, endpoint: Endpoint, msg: String, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        node.network().send(endpoint, encoder.encode(NetMessage::CardasciiEvent(msg)));
+    }
+
+    /// Shows a game event to every player (and to the table)
+    fn announce(&mut self, msg: String, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        let message = encoder.encode(NetMessage::CardasciiEvent(msg.clone()));
+        for endpoint in self.state.all_user_endpoints() {
+            node.network().send(*endpoint, message);
+        }
+        self.log_game_event(msg);
+    }
+
+    fn end_of_turn(&mut self, mut record: TurnRecord, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        if self.state.rules().sealed_answers {
+            self.reveal_attempts(node, encoder);
+        }
+        let values = record.cards.iter().map(|card| card.value).collect::<Vec<_>>();
+        let rules = self.state.rules();
+        let target = match &record.target_card {
+            Some(card) => f64::from(card.value),
+            None => rules.target_value(),
+        };
+        let solutions = solver::solutions(&values, target, rules, solver::MAX_SOLUTIONS);
+        record.solutions = solutions.found.len();
+        record.solutions_complete = solutions.complete;
+        let stats = match (&record.outcome, solutions.found.first()) {
+            (TurnOutcome::Tie, Some(solution)) => format!(
+                "this hand had {}, i.e. {}",
+                solutions.count_text(),
+                solution.pretty()
+            ),
+            (TurnOutcome::Tie, None) if solutions.complete => String::from("this hand had no solution"),
+            (TurnOutcome::Tie, None) => String::from("no solution was found for this hand"),
+            (TurnOutcome::Winner(..), _) => format!("this hand had {}", solutions.count_text()),
+        };
+        self.announce(stats, node, encoder);
+        self.broadcast_turn_result(record, node, encoder);
+        self.start_new_turn(node, encoder);
+    }
+
+    /// Sealed answers mode: all the answers of the turn are shown once the turn has ended
+    fn reveal_attempts(&mut self, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        let attempts = match &self.state.game24 {
+            Some(game) => game.attempts().clone(),
+            None => return,
+        };
+        if attempts.is_empty() {
+            return
+        }
+        self.announce(String::from("answers of this turn:"), node, encoder);
+        for attempt in attempts {
+            let date = chrono::Local
+                .timestamp_opt(attempt.timestamp, 0)
+                .single()
+                .map(|date| date.format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            let line = match attempt.error {
+                None => format!("[{}] {} > {} (correct)", date, attempt.user, attempt.answer),
+                Some(error) => {
+                    format!("[{}] {} > {} (incorrect: {})", date, attempt.user, attempt.answer, error)
+                }
+            };
+            self.announce(line, node, encoder);
+        }
+    }
+
+    fn start_new_turn(&mut self, node: &NodeHandler<Signal>, encoder: &mut Encoder) {
+        let solo = match self.state.solo_mut() {
+            Some(solo) if solo.is_finished() => {
+                let msg = format!("the daily puzzle is finished: {}", solo.result_line());
+                return self.log_game_event(msg)
+            }
+            Some(solo) => {
+                solo.start_hand();
+                true
+            }
+            None => false,
+        };
+        let game = match self.state.game24.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+        // The practice never ends, the cards are shuffled again when the deck runs out
+        if solo && game.status().deck_left < game.rules().cards + usize::from(game.rules().target_card) {
+            game.do_reset();
+        }
+        match game.do_give_cards() {
+            Ok(_) => {
+                if let Some(hand) = get_vec_gived_cards(game) {
+                    let target_card = game.target_card().cloned();
+                    let message =
+                        encoder.encode(NetMessage::CardasciiNewTurn(game.status(), hand, target_card));
+                    for endpoint in self.state.all_user_endpoints() {
+                        node.network().send(*endpoint, message);
+                    }
+                }
+                self.schedule_bots(node, encoder);
+            }
+            Err(Game24Err(msg)) => self.announce(msg, node, encoder),
+        }
+    }
+
+    fn broadcast_turn_result(
+        &mut self,
+        record: TurnRecord,
+        node: &NodeHandler<Signal>,
+        encoder: &mut Encoder,
+    ) {
+        let message = encoder.encode(NetMessage::CardasciiTurnResult(record.clone()));
+        for endpoint in self.state.all_user_endpoints() {
+            node.network().send(*endpoint, message);
+        }
+        self.state.add_turn_result(record);
+    }
+
     fn process_terminal_event(
         &mut self,
         term_event: TermEvent,
@@ -334,6 +903,19 @@ impl<'a> Application {
         encoder: &mut Encoder,
         renderer: &mut Renderer<Stdout>,
     ) {
+        if let TermEvent::Key(KeyEvent { code, modifiers }) = term_event {
+            if code == KeyCode::Tab {
+                self.state.toggle_answer_builder();
+                return
+            }
+            if self.state.answer_builder().is_some()
+                && !modifiers.contains(KeyModifiers::CONTROL)
+                && self.process_builder_key(code, node, encoder)
+            {
+                return
+            }
+        }
+
         match term_event {
             TermEvent::Mouse(_) => (),
             TermEvent::Resize(_, _) => {
@@ -346,29 +928,50 @@ impl<'a> Application {
                 KeyCode::Char(character) => {
                     if character == 'c' && modifiers.contains(KeyModifiers::CONTROL) {
                         node.signals().send_with_priority(Signal::Close(None));
+                    } else if character == 'n' && modifiers.contains(KeyModifiers::CONTROL) {
+                        self.state.search_move(SearchMovement::Next);
+                    } else if character == 'p' && modifiers.contains(KeyModifiers::CONTROL) {
+                        self.state.search_move(SearchMovement::Previous);
                     } else {
                         self.state.input_write(character);
+                        self.state.update_search_from_input();
                     }
                 }
                 KeyCode::Enter => {
                     if let Some(input) = self.state.reset_input() {
+                        if let Some(query) = input.strip_prefix(State::SEARCH_PREFIX) {
+                            // An empty search removes the current one
+                            self.state.set_search(Some(query.to_string()));
+                            return
+                        }
                         match self.commands.find_command_action(&input).transpose() {
                             Ok(action) => {
-                                let message = ChatMessage::new(
-                                    format!("{} (me)", self.config.user_name),
-                                    MessageType::Text(input.clone()),
-                                );
-                                self.state.add_message(message);
-
-                                for endpoint in self.state.all_user_endpoints() {
-                                    node.network().send(
-                                        *endpoint,
-                                        encoder.encode(NetMessage::UserMessage(input.clone())),
+                                if self.commands.is_echoed(&input) {
+                                    let message = ChatMessage::new(
+                                        format!("{} (me)", self.config.user_name),
+                                        MessageType::Text(input.clone()),
                                     );
+                                    self.state.add_message(message);
+                                    self.state.record_history(
+                                        HistoryKind::Chat,
+                                        &self.config.user_name,
+                                        &input,
+                                    );
+
+                                    for endpoint in self.state.all_user_endpoints() {
+                                        node.network().send(
+                                            *endpoint,
+                                            encoder.encode(NetMessage::UserMessage(input.clone())),
+                                        );
+                                    }
                                 }
 
                                 match action {
-                                    Some(action) => self.process_action(action, node),
+                                    Some(action) => {
+                                        self.process_action(action, node);
+                                        self.schedule_bots(node, encoder);
+                                        self.process_solo_moves(node, encoder);
+                                    }
                                     None => {
                                         if input.starts_with('?') {
                                             String::from("This command doesn't exists")
@@ -385,9 +988,11 @@ impl<'a> Application {
                 }
                 KeyCode::Delete => {
                     self.state.input_remove();
+                    self.state.update_search_from_input();
                 }
                 KeyCode::Backspace => {
                     self.state.input_remove_previous();
+                    self.state.update_search_from_input();
                 }
                 KeyCode::Left => {
                     self.state.input_move_cursor(CursorMovement::Left);
@@ -415,6 +1020,64 @@ impl<'a> Application {
         }
     }
 
+    /// Keys of the answer builder, returns false if the key is not used by the builder
+    fn process_builder_key(
+        &mut self,
+        code: KeyCode,
+        node: &NodeHandler<Signal>,
+        encoder: &mut Encoder,
+    ) -> bool {
+        let hand = self.state.hand();
+        let builder = match self.state.answer_builder_mut() {
+            Some(builder) => builder,
+            None => return false,
+        };
+        let result = match code {
+            KeyCode::Left => {
+                builder.select_previous(hand.len());
+                Ok(())
+            }
+            KeyCode::Right => {
+                builder.select_next(hand.len());
+                Ok(())
+            }
+            KeyCode::Char(' ') => builder.push_selected(hand.len()),
+            KeyCode::Char(c @ '1'..='9') => {
+                builder.push(Piece::Card(c as usize - '1' as usize), hand.len())
+            }
+            KeyCode::Char('r') => builder.push(Piece::Operator('√'), hand.len()),
+            KeyCode::Char(c) => builder.push(Piece::Operator(c), hand.len()),
+            KeyCode::Backspace => {
+                builder.pop();
+                Ok(())
+            }
+            KeyCode::Delete => {
+                builder.clear();
+                Ok(())
+            }
+            KeyCode::Enter => {
+                if builder.is_empty() {
+                    return true
+                }
+                let answer = builder.expression(&hand);
+                builder.clear();
+                match CardasciiAnswer::new(answer) {
+                    Ok(action) => {
+                        self.process_action(Box::new(action), node);
+                        self.process_solo_moves(node, encoder);
+                    }
+                    Err(e) => e.report_err(&mut self.state),
+                }
+                Ok(())
+            }
+            _ => return false,
+        };
+        if let Err(e) = result {
+            e.report_err(&mut self.state);
+        }
+        true
+    }
+
     fn try_new_turn_game24(&mut self) {
         if let Some(game) = &mut self.state.game24 {
             game.do_give_cards();
@@ -482,41 +1145,66 @@ pub fn read_ws<'a>(
             if let Ok(encoder_guard) = &mut _2_encoder_arc.lock() {
                 if let Ok(node_guard) = &_3_node_arc.lock() {
                     if let Ok(renderer_guard) = &mut _4_renderer_arc.lock() {
-                        match event.network() {
-                            NetEvent::Connected(endpoint, _) => {
-                                //app_guard.log_in_chat(format!("connected! <{endpoint:?}"));
+                        match event {
+                            NodeEvent::Network(net_event) => match net_event {
+                                NetEvent::Connected(endpoint, _) => {
+                                    //app_guard.log_in_chat(format!("connected! <{endpoint:?}"));
                 
-                                let message = NetMessage::HelloServer(
-                                    app_guard.config.user_name.clone(),
-                                    endpoint.addr().port(),
-                                );
+                                    let message = NetMessage::HelloServer(
+                                        app_guard.config.user_name.clone(),
+                                        endpoint.addr().port(),
+                                    );
                 
-                                node_guard.network().send(endpoint, encoder_guard.encode(message));
-                            }
-                            NetEvent::Message(endpoint, message) => match encoder::decode(&message) {
-                                Some(net_message) => {
-                                    app_guard.process_network_message(
-                                        endpoint,
-                                        net_message,
+                                    node_guard.network().send(endpoint, encoder_guard.encode(message));
+                                }
+                                NetEvent::Message(endpoint, message) => match encoder::decode(&message) {
+                                    Some(net_message) => {
+                                        app_guard.process_network_message(
+                                            endpoint,
+                                            net_message,
+                                            node_guard,
+                                            encoder_guard,
+                                        );
+                                    }
+                                    None =>
+                                    /*return Err("Unknown message received".into())*/
+                                    {
+                                        ()
+                                    }
+                                },
+                                NetEvent::Accepted(_, _resource_id) => {
+                                    //app_guard.log_in_chat(format!("accepted! <{endpoint:?}"));
+                                }
+                                NetEvent::Disconnected(endpoint) => {
+                                    app_guard.process_disconnection(endpoint, node_guard, encoder_guard);
+                                    app_guard.righ_the_bell();
+                                }
+                            },
+                            NodeEvent::Signal(signal) => match signal {
+                                Signal::Action(action) => {
+                                    app_guard.process_action(action, node_guard);
+                                }
+                                Signal::Bot(name, turn, bot_move) => {
+                                    app_guard.process_bot_move(
+                                        name,
+                                        turn,
+                                        bot_move,
                                         node_guard,
                                         encoder_guard,
                                     );
                                 }
-                                None =>
-                                /*return Err("Unknown message received".into())*/
-                                {
-                                    ()
+                                Signal::Terminal(term_event) => {
+                                    app_guard.process_terminal_event(
+                                        term_event,
+                                        node_guard,
+                                        encoder_guard,
+                                        renderer_guard,
+                                    );
+                                }
+                                Signal::Close(_) => {
+                                    node_guard.stop();
                                 }
                             },
-                            NetEvent::Accepted(_, _resource_id) => {
-                                //app_guard.log_in_chat(format!("accepted! <{endpoint:?}"));
-                            }
-                            NetEvent::Disconnected(endpoint) => {
-                                app_guard.state.disconnected_user(endpoint);
-                                //If the endpoint was sending a stream make sure to close its window
-                                app_guard.state.windows.remove(&endpoint);
-                                app_guard.righ_the_bell();
-                            }
                         }
                         renderer_guard.render(&app_guard.state, &app_guard.config.theme);
                     }
@@ -557,6 +1245,7 @@ pub fn run_app(config: Config) {
                 let my_addr = format!("0.0.0.0:{}", port).parse::<SocketAddrV4>().unwrap();
                 let (_, _) = node_guard.network().listen(Transport::Ws, my_addr).unwrap();
             }
+            NodeType::Solo => (),
         }
     }
     let app_arc = Arc::clone(&_1_app_arc);
@@ -611,14 +1300,41 @@ pub fn run_app(config: Config) {
     t2.join().unwrap();
 }
 
-fn get_vec_gived_cards(game: & Game24) -> HandCardData {
-    game.get_gived_cards()
+/// Installs the card art pack chosen in the config, the cards without a valid file
+/// keep their built-in art
+fn load_card_pack(name: &str, state: &mut State) {
+    let dir = match CardPack::dir(name) {
+        Some(dir) => dir,
+        None => return "Unable to find the config dir to load the card pack".to_string().report_err(state),
+    };
+    match CardPack::load(&dir) {
+        Ok((pack, problems)) => {
+            for problem in problems {
+                format!("Card pack '{}': {}", name, problem).report_warn(state);
+            }
+            format!("Card pack '{}': {} files loaded", name, pack.file_count()).report_info(state);
+            pack.install();
+        }
+        Err(e) => format!("Unable to load the card pack '{}' from {}: {}", name, dir.display(), e)
+            .report_err(state),
+    }
+}
+
+/// Rejected answer with the steps followed by the table to check it
+fn rejection_text(user: &str, explanation: &AnswerExplanation) -> String {
+    format!("{user}: isn't correct answer!! =_= > {explanation}")
+}
+
+/// None if there is no complete hand dealt (i.e. the deck is empty)
+fn get_vec_gived_cards(game: & Game24) -> Option<HandCardData> {
+    let cards = game.get_gived_cards()
         .into_iter()
         .map(|card|  *card)
-        .collect::<Vec<Card>>()
-        .try_into()
-        .unwrap_or_else(|v: Vec<Card>| panic!("Expected a Vec of length {} but it was {}", 4, v.len()))
-
+        .collect::<Vec<Card>>();
+    match cards.len() == game.rules().cards {
+        true => Some(cards),
+        false => None,
+    }
 }
 /*
 struct AppOperation {
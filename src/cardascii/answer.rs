@@ -0,0 +1,856 @@
+use rcalc::{ASTNode, Lexer, Parser, Token};
+use serde::{Serialize, Deserialize};
+use super::common::Card;
+use super::rules::Rules;
+
+/// Letters to reference the cards by their position in the hand, following the layout
+/// of the cards panel (two cards per row): `a` top left, `b` top right, `c` below `a`...
+/// The same cards can also be referenced as `#1`, `#2`, `#3`...
+pub const POSITION_LETTERS: [char; Rules::MAX_CARDS] = ['a', 'b', 'c', 'd', 'e', 'f'];
+
+/// Biggest value allowed in the intermediate operations of an answer,
+/// powers and factorials grow too fast to be checked beyond it.
+pub const MAX_VALUE: f64 = 1e9;
+
+/// Biggest factorial allowed, 12! is the last one below `MAX_VALUE`
+pub const MAX_FACTORIAL: f64 = 12.0;
+
+/// Writes the answer with the symbols understood by rcalc: operator aliases (`x`, `×`, `÷`, `:`...)
+/// and full-width characters are replaced, and the whitespace is collapsed.
+/// Square roots are written as `√`, also when the answer uses `sqrt`.
+pub fn normalize(answer: &str) -> Result<String, String> {
+    let answer = answer
+        .chars()
+        .map(|c| match c {
+            // Full-width forms, as written by some input methods
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            c => c,
+        })
+        .collect::<String>();
+    let chars = answer.replace("sqrt", "√").chars().collect::<Vec<_>>();
+
+    let mut out = String::with_capacity(answer.len());
+    for (i, c) in chars.iter().enumerate() {
+        let in_word = |i: Option<usize>| {
+            i.and_then(|i| chars.get(i)).map(|c| c.is_alphabetic()).unwrap_or(false)
+        };
+        let normalized = match *c {
+            // `x` is a multiplication unless it is part of a word
+            'x' | 'X' if !in_word(i.checked_sub(1)) && !in_word(Some(i + 1)) => '*',
+            '×' | '·' | '⋅' | '∗' => '*',
+            '÷' | ':' | '∕' => '/',
+            '−' | '–' | '—' => '-',
+            '[' | '{' => '(',
+            ']' | '}' => ')',
+            c if c.is_whitespace() => ' ',
+            c if c.is_ascii_digit() || c.is_ascii_alphabetic() => c,
+            c @ ('+' | '-' | '*' | '/' | '(' | ')' | '#' | '^' | '!' | '√') => c,
+            c => {
+                return Err(format!(
+                    "'{c}' is not supported, write the operation with + - * / ( ) \
+                     and the values or positions (a b c...) of the cards"
+                ))
+            }
+        };
+        if normalized == ' ' && (out.is_empty() || out.ends_with(' ')) {
+            continue
+        }
+        out.push(normalized);
+    }
+    Ok(out.trim_end().to_string())
+}
+
+/// Answer with its positional references replaced by the values of the cards
+pub struct ResolvedAnswer {
+    pub expression: String,
+    /// Positions of the cards referenced in the answer
+    pub positions: Vec<usize>,
+}
+
+/// With `concatenation` several letters together join the values of their cards:
+/// `ab` is `12` when `a` is 1 and `b` is 2.
+pub fn resolve_positions(
+    answer: &str,
+    cards: &[Card],
+    concatenation: bool,
+) -> Result<ResolvedAnswer, String> {
+    let mut expression = String::with_capacity(answer.len());
+    let mut positions = Vec::new();
+    let mut chars = answer.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let (reference, references) = if c == '#' {
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+            let position = digits
+                .parse::<usize>()
+                .ok()
+                .filter(|n| (1..=cards.len()).contains(n))
+                .ok_or(format!("'#{digits}' is not a card, use #1 to #{}", cards.len()))?;
+            (format!("#{digits}"), vec![position - 1])
+        } else if c.is_alphabetic() {
+            let mut word = c.to_string();
+            while let Some(letter) = chars.next_if(|c| c.is_alphabetic()) {
+                word.push(letter);
+            }
+            let letters = word
+                .chars()
+                .map(|c| POSITION_LETTERS.iter().position(|l| *l == c.to_ascii_lowercase()))
+                .collect::<Option<Vec<_>>>()
+                .filter(|letters| letters.iter().all(|position| *position < cards.len()));
+            match letters {
+                Some(letters) if letters.len() == 1 || concatenation => (word, letters),
+                _ => {
+                    return Err(format!(
+                        "'{word}' is not a card, use a to {} (or #1 to #{}) for their positions",
+                        POSITION_LETTERS[cards.len().clamp(1, Rules::MAX_CARDS) - 1],
+                        cards.len()
+                    ))
+                }
+            }
+        } else {
+            expression.push(c);
+            continue
+        };
+
+        for position in references {
+            if positions.contains(&position) {
+                return Err(format!("the card {reference} is used more than once"))
+            }
+            positions.push(position);
+            expression.push_str(&cards[position].value.to_string());
+        }
+    }
+
+    Ok(ResolvedAnswer { expression, positions })
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Operator {
+    #[serde(rename = "+")]
+    Add,
+    #[serde(rename = "-")]
+    Sub,
+    #[serde(rename = "*")]
+    Mul,
+    #[serde(rename = "/")]
+    Div,
+    #[serde(rename = "^")]
+    Pow,
+    #[serde(rename = "sqrt")]
+    Sqrt,
+    #[serde(rename = "!")]
+    Factorial,
+    /// Joins the digits of the cards, written by putting their values (or positions) together
+    #[serde(rename = "concat")]
+    Concat,
+}
+
+impl Operator {
+    pub fn pretty_symbol(&self) -> char {
+        match self {
+            Operator::Mul => '×',
+            Operator::Div => '÷',
+            operator => operator.symbol(),
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Sub => '-',
+            Operator::Mul => '*',
+            Operator::Div => '/',
+            Operator::Pow => '^',
+            Operator::Sqrt => '√',
+            Operator::Factorial => '!',
+            Operator::Concat => '&',
+        }
+    }
+
+    /// Square root and factorial take a single operand
+    pub fn is_unary(&self) -> bool {
+        matches!(self, Operator::Sqrt | Operator::Factorial)
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Sub => 1,
+            Operator::Mul | Operator::Div => 2,
+            Operator::Pow => 3,
+            Operator::Sqrt | Operator::Factorial | Operator::Concat => 4,
+        }
+    }
+
+    /// Result of a binary operator, or why it can't be applied
+    pub fn apply(&self, a: f64, b: f64) -> Result<f64, &'static str> {
+        let result = match self {
+            Operator::Add => a + b,
+            Operator::Sub => a - b,
+            Operator::Mul => a * b,
+            Operator::Div if b == 0.0 => return Err("is a division by zero"),
+            Operator::Div => a / b,
+            Operator::Pow => a.powf(b),
+            Operator::Concat if a < 0.0 || b < 0.0 || a.fract() != 0.0 || b.fract() != 0.0 => {
+                return Err("only joins whole numbers")
+            }
+            Operator::Concat => a * 10f64.powi(b.to_string().len() as i32) + b,
+            Operator::Sqrt | Operator::Factorial => return Err("needs a single operand"),
+        };
+        Self::check(result)
+    }
+
+    /// Result of an unary operator, or why it can't be applied
+    pub fn apply_unary(&self, a: f64) -> Result<f64, &'static str> {
+        let result = match self {
+            Operator::Sqrt if a < 0.0 => return Err("is the root of a negative number"),
+            Operator::Sqrt => a.sqrt(),
+            Operator::Factorial if a < 0.0 || a.fract() != 0.0 => {
+                return Err("needs a whole number that is not negative")
+            }
+            Operator::Factorial if a > MAX_FACTORIAL => return Err("is too big"),
+            Operator::Factorial => (1..=a as u64).product::<u64>() as f64,
+            _ => return Err("needs two operands"),
+        };
+        Self::check(result)
+    }
+
+    fn check(result: f64) -> Result<f64, &'static str> {
+        match result {
+            result if result.is_nan() => Err("is not a real number"),
+            result if !result.is_finite() || result.abs() > MAX_VALUE => Err("is too big"),
+            result => Ok(result),
+        }
+    }
+}
+
+/// Operation of an answer, parsed by rcalc
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Number(usize),
+    Negative(Box<Expr>),
+    Unary(Operator, Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Biggest number accepted in an answer, rcalc panics with numbers that don't fit in an usize
+    const MAX_DIGITS: usize = 9;
+
+    pub fn parse(expression: &str) -> Result<Expr, String> {
+        if expression.trim().is_empty() {
+            return Err(String::from("the answer is empty"))
+        }
+        if let Some(number) = expression
+            .split(|c: char| !c.is_ascii_digit())
+            .find(|number| number.len() > Self::MAX_DIGITS)
+        {
+            return Err(format!("the number {number} is too big"))
+        }
+
+        let expression = &Self::mark_roots(&expression.chars().collect::<Vec<_>>())?;
+        let mut lexer = Lexer::from(expression.as_str());
+        let (mut numbers, mut opened, mut closed) = (0, 0, 0);
+        loop {
+            match lexer.next_token() {
+                Ok(Token::EOF) => break,
+                Ok(Token::NUMBER(_)) => numbers += 1,
+                // The marks of the square roots have a 0 that is not part of the answer
+                Ok(Token::MODULO) => numbers -= 1,
+                Ok(Token::LPAREN) => opened += 1,
+                Ok(Token::RPAREN) => closed += 1,
+                Ok(_) => (),
+                Err(e) => return Err(format!("the operation isn't correct: {e}")),
+            }
+        }
+        if opened != closed {
+            return Err(format!("there are {opened} '(' and {closed} ')'"))
+        }
+
+        let tree = Parser::from(Lexer::from(expression.as_str()))
+            .parse()
+            .map_err(|_| String::from("the operation isn't correct: an operator is in a wrong place"))?;
+        let expr = Expr::from_node(&tree)?;
+        if expr.numbers().len() != numbers {
+            return Err(String::from("the operation isn't correct: there is an operator missing"))
+        }
+        Ok(expr)
+    }
+
+    /// rcalc doesn't know square roots: `√x` is marked as `(0%x)`, as `%` can't be written
+    /// in the answers. The root applies to the number, parenthesis or root that follows it.
+    fn mark_roots(chars: &[char]) -> Result<String, String> {
+        let mut out = String::with_capacity(chars.len());
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == '√' {
+                let end = Self::root_operand_end(chars, i + 1)?;
+                out.push_str(&format!("(0%{})", Self::mark_roots(&chars[i + 1..end])?));
+                i = end;
+            } else {
+                out.push(chars[i]);
+                i += 1;
+            }
+        }
+        Ok(out)
+    }
+
+    fn root_operand_end(chars: &[char], start: usize) -> Result<usize, String> {
+        let mut i = start;
+        while chars.get(i).map(|c| c.is_whitespace()).unwrap_or(false) {
+            i += 1;
+        }
+        match chars.get(i) {
+            Some(c) if c.is_ascii_digit() => {
+                while chars.get(i).map(char::is_ascii_digit).unwrap_or(false) {
+                    i += 1;
+                }
+                Ok(i)
+            }
+            Some('(') => {
+                let mut depth = 0;
+                for (end, c) in chars.iter().enumerate().skip(i) {
+                    match c {
+                        '(' => depth += 1,
+                        ')' if depth == 1 => return Ok(end + 1),
+                        ')' => depth -= 1,
+                        _ => (),
+                    }
+                }
+                Err(String::from("there are more '(' than ')'"))
+            }
+            Some('√') => Self::root_operand_end(chars, i + 1),
+            _ => Err(String::from("√ must be followed by a number or a parenthesis")),
+        }
+    }
+
+    fn from_node(node: &ASTNode) -> Result<Expr, String> {
+        let children = node
+            .children()
+            .iter()
+            .map(Expr::from_node)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut children = children.into_iter();
+
+        let expr = match (node.token(), children.len()) {
+            (Token::NUMBER(n), 0) => Expr::Number(*n),
+            (Token::PLUS, 1) => children.next().unwrap(),
+            (Token::MINUS, 1) => Expr::Negative(Box::new(children.next().unwrap())),
+            (Token::FACTORIAL, 1) => Expr::Unary(Operator::Factorial, Box::new(children.next().unwrap())),
+            // Square root marked by `mark_roots`
+            (Token::MODULO, 2) => Expr::Unary(Operator::Sqrt, Box::new(children.nth(1).unwrap())),
+            (token, 2) => {
+                let operator = match token {
+                    Token::PLUS => Operator::Add,
+                    Token::MINUS => Operator::Sub,
+                    Token::MULTIPLY => Operator::Mul,
+                    Token::DIVIDE => Operator::Div,
+                    Token::EXPONENT => Operator::Pow,
+                    token => return Err(format!("the operator {} is not allowed", token_symbol(token))),
+                };
+                let left = children.next().unwrap();
+                let right = children.next().unwrap();
+                Expr::Binary(operator, Box::new(left), Box::new(right))
+            }
+            (token, _) => return Err(format!("the operator {} is not allowed", token_symbol(token))),
+        };
+        Ok(expr)
+    }
+
+    /// Operators used in the operation, a negative number is a subtraction
+    pub fn operators(&self) -> Vec<Operator> {
+        match self {
+            Expr::Number(_) => Vec::new(),
+            Expr::Negative(expr) => {
+                let mut operators = expr.operators();
+                operators.push(Operator::Sub);
+                operators
+            }
+            Expr::Unary(operator, expr) => {
+                let mut operators = expr.operators();
+                operators.push(*operator);
+                operators
+            }
+            Expr::Binary(operator, left, right) => {
+                let mut operators = left.operators();
+                operators.push(*operator);
+                operators.extend(right.operators());
+                operators
+            }
+        }
+    }
+
+    /// Numbers of the operation, from left to right
+    pub fn numbers(&self) -> Vec<usize> {
+        match self {
+            Expr::Number(n) => vec![*n],
+            Expr::Negative(expr) | Expr::Unary(_, expr) => expr.numbers(),
+            Expr::Binary(_, left, right) => {
+                let mut numbers = left.numbers();
+                numbers.extend(right.numbers());
+                numbers
+            }
+        }
+    }
+
+    pub fn value(&self) -> Result<f64, String> {
+        self.evaluate(&mut Vec::new())
+    }
+
+    /// Evaluates the operation writing down each intermediate operation
+    pub fn evaluate(&self, steps: &mut Vec<Step>) -> Result<f64, String> {
+        match self {
+            Expr::Number(n) => Ok(*n as f64),
+            Expr::Negative(expr) => {
+                let value = expr.evaluate(steps)?;
+                steps.push(Step { operation: format!("-({})", format_value(value)), result: -value });
+                Ok(-value)
+            }
+            Expr::Unary(operator, expr) => {
+                let value = expr.evaluate(steps)?;
+                let operation = match operator {
+                    Operator::Factorial => format!("{}!", format_value(value)),
+                    operator => format!("{}{}", operator.symbol(), format_value(value)),
+                };
+                let result = operator
+                    .apply_unary(value)
+                    .map_err(|problem| format!("{operation} {problem}"))?;
+                steps.push(Step { operation, result });
+                Ok(result)
+            }
+            Expr::Binary(operator, left, right) => {
+                let left = left.evaluate(steps)?;
+                let right = right.evaluate(steps)?;
+                let operation =
+                    format!("{} {} {}", format_value(left), operator.symbol(), format_value(right));
+                let result = operator
+                    .apply(left, right)
+                    .map_err(|problem| format!("{operation} {problem}"))?;
+                steps.push(Step { operation, result });
+                Ok(result)
+            }
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(operator, _, _) | Expr::Unary(operator, _) => operator.precedence(),
+            _ => u8::MAX,
+        }
+    }
+
+    /// Writes the operation with only the needed parentheses
+    fn write(&self, out: &mut String, pretty: bool) {
+        match self {
+            Expr::Number(n) => out.push_str(&n.to_string()),
+            Expr::Negative(expr) if expr.precedence() == u8::MAX => {
+                out.push('-');
+                expr.write(out, pretty);
+            }
+            Expr::Negative(expr) => {
+                out.push_str("-(");
+                expr.write(out, pretty);
+                out.push(')');
+            }
+            // The root applies to the number, parenthesis or root that follows it
+            Expr::Unary(Operator::Sqrt, expr) => {
+                out.push(Operator::Sqrt.symbol());
+                let parens = !matches!(**expr, Expr::Number(_) | Expr::Unary(Operator::Sqrt, _));
+                expr.write_operand(out, pretty, parens);
+            }
+            Expr::Unary(operator, expr) => {
+                let parens = !matches!(**expr, Expr::Number(_) | Expr::Unary(..));
+                expr.write_operand(out, pretty, parens);
+                out.push(operator.symbol());
+            }
+            Expr::Binary(operator, left, right) => {
+                let precedence = operator.precedence();
+                // a ^ b ^ c is (a ^ b) ^ c for rcalc, but nobody reads it that way
+                let left_needs_parens = left.precedence() < precedence
+                    || (left.precedence() == precedence && *operator == Operator::Pow);
+                left.write_operand(out, pretty, left_needs_parens);
+                let symbol = match pretty {
+                    true => operator.pretty_symbol(),
+                    false => operator.symbol(),
+                };
+                out.push_str(&format!(" {symbol} "));
+                // a - (b + c), a / (b * c) and a ^ (b ^ c) need the parentheses
+                let right_needs_parens = right.precedence() < precedence
+                    || (right.precedence() == precedence
+                        && matches!(operator, Operator::Sub | Operator::Div | Operator::Pow));
+                right.write_operand(out, pretty, right_needs_parens);
+            }
+        }
+    }
+
+    fn write_operand(&self, out: &mut String, pretty: bool, parens: bool) {
+        if parens {
+            out.push('(');
+        }
+        self.write(out, pretty);
+        if parens {
+            out.push(')');
+        }
+    }
+
+    /// Operation written with × and ÷
+    pub fn pretty(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, true);
+        out
+    }
+
+    /// Same operation written in a unique way: the operands of the chains of additions and
+    /// subtractions (or multiplications and divisions) are sorted, so two answers are the same
+    /// solution up to commutativity and associativity when their canonical forms are equal.
+    pub fn canonical(&self) -> Expr {
+        let (mut direct, mut inverse) = (Vec::new(), Vec::new());
+        match self {
+            Expr::Number(n) => Expr::Number(*n),
+            Expr::Unary(operator, expr) => Expr::Unary(*operator, Box::new(expr.canonical())),
+            Expr::Binary(Operator::Pow, left, right) => {
+                Expr::Binary(Operator::Pow, Box::new(left.canonical()), Box::new(right.canonical()))
+            }
+            Expr::Binary(operator @ (Operator::Mul | Operator::Div), ..) => {
+                self.collect_operands(*operator, false, &mut direct, &mut inverse);
+                Expr::chain(direct, inverse, Operator::Mul, Operator::Div)
+            }
+            _ => {
+                self.collect_operands(Operator::Add, false, &mut direct, &mut inverse);
+                Expr::chain(direct, inverse, Operator::Add, Operator::Sub)
+            }
+        }
+    }
+
+    pub fn is_same_solution(&self, other: &Expr) -> bool {
+        self.canonical().to_string() == other.canonical().to_string()
+    }
+
+    /// Collects the operands of a chain of operations with the same precedence as `operator`,
+    /// separating the ones that are added (or multiplied) and the ones that are subtracted
+    /// (or divided).
+    fn collect_operands(
+        &self,
+        operator: Operator,
+        inverted: bool,
+        direct: &mut Vec<Expr>,
+        inverse: &mut Vec<Expr>,
+    ) {
+        let additive = operator.precedence() == Operator::Add.precedence();
+        match self {
+            Expr::Negative(expr) if additive => {
+                expr.collect_operands(operator, !inverted, direct, inverse)
+            }
+            Expr::Binary(op, left, right) if op.precedence() == operator.precedence() => {
+                left.collect_operands(operator, inverted, direct, inverse);
+                let inverts = matches!(op, Operator::Sub | Operator::Div);
+                right.collect_operands(operator, inverted != inverts, direct, inverse);
+            }
+            expr => match inverted {
+                false => direct.push(expr.canonical()),
+                true => inverse.push(expr.canonical()),
+            },
+        }
+    }
+
+    fn chain(mut direct: Vec<Expr>, mut inverse: Vec<Expr>, operator: Operator, inverse_operator: Operator) -> Expr {
+        direct.sort_by(Expr::order);
+        inverse.sort_by(Expr::order);
+        let mut inverse = inverse.into_iter();
+        let mut direct = direct.into_iter();
+        let first = match direct.next() {
+            Some(expr) => expr,
+            // Only happens with subtractions: -a - b
+            None => Expr::Negative(Box::new(inverse.next().unwrap_or(Expr::Number(0)))),
+        };
+        let expr = direct.fold(first, |acc, expr| Expr::Binary(operator, Box::new(acc), Box::new(expr)));
+        inverse.fold(expr, |acc, expr| Expr::Binary(inverse_operator, Box::new(acc), Box::new(expr)))
+    }
+
+    /// Numbers first, from the biggest to the smallest, then the other operations
+    fn order(a: &Expr, b: &Expr) -> std::cmp::Ordering {
+        match (a, b) {
+            (Expr::Number(a), Expr::Number(b)) => b.cmp(a),
+            (Expr::Number(_), _) => std::cmp::Ordering::Less,
+            (_, Expr::Number(_)) => std::cmp::Ordering::Greater,
+            _ => a.to_string().cmp(&b.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut out = String::new();
+        self.write(&mut out, false);
+        write!(f, "{out}")
+    }
+}
+
+fn token_symbol(token: &Token) -> &'static str {
+    match token {
+        Token::PLUS => "+",
+        Token::MINUS => "-",
+        Token::MULTIPLY => "*",
+        Token::DIVIDE => "/",
+        Token::DIVIDEINT => "./",
+        Token::MODULO => "%",
+        Token::EXPONENT => "^",
+        Token::FACTORIAL => "!",
+        _ => "?",
+    }
+}
+
+/// Values are shown with up to 4 decimals
+pub fn format_value(value: f64) -> String {
+    let text = format!("{value:.4}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => String::from("0"),
+        text => text.to_string(),
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Step {
+    pub operation: String,
+    pub result: f64,
+}
+
+/// Explanation of how the table checked an answer, so the player can see where it went wrong
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AnswerExplanation {
+    /// Answer as it was sent
+    pub answer: String,
+    /// Operation understood by the table, with the values of the cards
+    pub expression: Option<String>,
+    pub steps: Vec<Step>,
+    pub value: Option<f64>,
+    pub used_cards: Vec<u8>,
+    pub missing_cards: Vec<u8>,
+    /// Numbers of the answer that are not in the cards
+    pub unknown_numbers: Vec<usize>,
+    /// Why the answer was rejected, `None` if it is correct
+    pub problem: Option<String>,
+}
+
+impl AnswerExplanation {
+    /// Explanation of an answer rejected before looking at the operation
+    pub fn rejected(answer: &str, problem: String) -> AnswerExplanation {
+        AnswerExplanation {
+            answer: answer.to_string(),
+            problem: Some(problem),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_correct(&self) -> bool {
+        self.problem.is_none()
+    }
+}
+
+impl std::fmt::Display for AnswerExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.expression.as_deref().unwrap_or(&self.answer))?;
+        if let Some(problem) = &self.problem {
+            write!(f, "\n  problem: {problem}")?;
+        }
+        for step in &self.steps {
+            write!(f, "\n  {} = {}", step.operation, format_value(step.result))?;
+        }
+        let values = |values: &Vec<u8>| {
+            values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(" ")
+        };
+        if !self.used_cards.is_empty() {
+            write!(f, "\n  cards used: {}", values(&self.used_cards))?;
+        }
+        if !self.missing_cards.is_empty() {
+            write!(f, "\n  cards missing: {}", values(&self.missing_cards))?;
+        }
+        if !self.unknown_numbers.is_empty() {
+            let numbers = self.unknown_numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+            write!(f, "\n  not in the cards: {}", numbers.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Operation of an answer, with the cards referenced by position replaced by their values
+pub fn parse_answer(answer: &str, cards: &[Card], rules: &Rules) -> Result<Expr, String> {
+    let concatenation = rules.allows(Operator::Concat);
+    Expr::parse(&resolve_positions(&normalize(answer)?, cards, concatenation)?.expression)
+}
+
+/// Looks for the cards whose values written together are `number`, i.e. 1 and 2 for 12,
+/// leaving their positions in `values` in `used`.
+fn concatenated_cards(number: &str, values: &[u8], used: &mut Vec<usize>) -> bool {
+    if number.is_empty() {
+        return used.len() > 1
+    }
+    for (position, value) in values.iter().enumerate() {
+        let digits = value.to_string();
+        if used.contains(&position) || !number.starts_with(&digits) {
+            continue
+        }
+        used.push(position);
+        if concatenated_cards(&number[digits.len()..], values, used) {
+            return true
+        }
+        used.pop();
+    }
+    false
+}
+
+/// Checks that an answer reaches the target with the cards of the hand and the rules of the game
+pub fn explain(answer: &str, cards: &[Card], target: f64, rules: &Rules) -> AnswerExplanation {
+    let concatenation = rules.allows(Operator::Concat);
+    let resolved = match normalize(answer)
+        .and_then(|answer| resolve_positions(&answer, cards, concatenation))
+    {
+        Ok(resolved) => resolved,
+        Err(problem) => return AnswerExplanation::rejected(answer, problem),
+    };
+    let expr = match Expr::parse(&resolved.expression) {
+        Ok(expr) => expr,
+        Err(problem) => return AnswerExplanation::rejected(answer, problem),
+    };
+    if let Some(operator) = expr.operators().into_iter().find(|operator| !rules.allows(*operator)) {
+        let problem = format!("the operator {} is not allowed in this game", operator.symbol());
+        return AnswerExplanation::rejected(answer, problem)
+    }
+
+    let mut explanation = AnswerExplanation {
+        answer: answer.to_string(),
+        expression: Some(expr.to_string()),
+        ..Default::default()
+    };
+
+    // The cards referenced by position are used for sure, their values are in the expression
+    let mut remaining = cards.iter().map(|card| card.value).collect::<Vec<_>>();
+    let mut referenced = Vec::new();
+    let mut positions = resolved.positions;
+    positions.sort_unstable_by(|a, b| b.cmp(a));
+    for position in positions {
+        referenced.push(remaining.remove(position));
+    }
+    for number in expr.numbers() {
+        if let Some(pos) = referenced.iter().position(|value| usize::from(*value) == number) {
+            explanation.used_cards.push(referenced.remove(pos));
+        } else if let Some(pos) = remaining.iter().position(|value| usize::from(*value) == number) {
+            explanation.used_cards.push(remaining.remove(pos));
+        } else if concatenation {
+            // The cards referenced by position go first, then the rest
+            let mut values = referenced.clone();
+            values.extend(remaining.iter());
+            let mut used = Vec::new();
+            match concatenated_cards(&number.to_string(), &values, &mut used) {
+                true => {
+                    explanation.used_cards.extend(used.iter().map(|position| values[*position]));
+                    used.sort_unstable_by(|a, b| b.cmp(a));
+                    for position in used {
+                        match position < referenced.len() {
+                            true => referenced.remove(position),
+                            false => remaining.remove(position - referenced.len()),
+                        };
+                    }
+                }
+                false => explanation.unknown_numbers.push(number),
+            }
+        } else {
+            explanation.unknown_numbers.push(number);
+        }
+    }
+    explanation.missing_cards = remaining;
+
+    let value = expr.evaluate(&mut explanation.steps);
+    explanation.value = value.as_ref().ok().cloned();
+
+    explanation.problem = if let Err(problem) = value {
+        Some(problem)
+    } else if !explanation.unknown_numbers.is_empty() {
+        Some(String::from("you are using numbers that aren't in the cards"))
+    } else if !explanation.missing_cards.is_empty() {
+        Some(String::from("you must use all the cards"))
+    } else {
+        match explanation.value {
+            Some(value) if (value - target).abs() < 1e-9 => None,
+            Some(value) => Some(format!(
+                "the result is {}, not {}",
+                format_value(value),
+                format_value(target)
+            )),
+            None => None,
+        }
+    };
+    explanation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cardascii::common::CardType;
+
+    fn expr(expression: &str) -> Expr {
+        Expr::parse(expression).unwrap()
+    }
+
+    fn hand(values: &[u8]) -> Vec<Card> {
+        values.iter().map(|value| Card { _type: CardType::Gold, value: *value }).collect()
+    }
+
+    #[test]
+    fn same_solution_up_to_commutativity() {
+        assert!(expr("3*8*(2-1)").is_same_solution(&expr("(2-1)*8*3")));
+        assert!(expr("8+4*(5-1)").is_same_solution(&expr("(5-1)*4+8")));
+        assert_eq!(expr("3*8*(2-1)").canonical().to_string(), expr("(2-1)*8*3").canonical().to_string());
+    }
+
+    #[test]
+    fn same_solution_up_to_associativity() {
+        assert!(expr("8/(2/6)").is_same_solution(&expr("8*6/2")));
+        assert!(expr("8-(2-6)").is_same_solution(&expr("8+6-2")));
+        assert!(expr("-3+27").is_same_solution(&expr("27-3")));
+    }
+
+    #[test]
+    fn different_solutions() {
+        assert!(!expr("8/(2/6)").is_same_solution(&expr("8/2/6")));
+        assert!(!expr("8-(2-6)").is_same_solution(&expr("8-2-6")));
+        assert!(!expr("(1+2)*8").is_same_solution(&expr("1+2*8")));
+        assert!(!expr("3*8").is_same_solution(&expr("3+8")));
+    }
+
+    #[test]
+    fn operator_aliases() {
+        assert_eq!(normalize("3 x 8 ÷ (2 − 1)").unwrap(), "3 * 8 / (2 - 1)");
+        assert_eq!(normalize("3×8:[1·1]").unwrap(), "3*8/(1*1)");
+        assert_eq!(normalize("sqrt(9)").unwrap(), "√(9)");
+        assert!(normalize("3 % 8").is_err());
+    }
+
+    #[test]
+    fn full_width_input() {
+        assert_eq!(normalize("（３＋５）＊３").unwrap(), "(3+5)*3");
+        assert_eq!(normalize("ａ\u{3000}＊\u{3000}ｂ").unwrap(), "a * b");
+    }
+
+    #[test]
+    fn positions_with_duplicate_values() {
+        let cards = hand(&[3, 3, 8, 1]);
+        // The value written takes the card that is not referenced by its position
+        assert!(explain("a*c*3/d", &cards, 72.0, &Rules::default()).is_correct());
+        assert!(explain("#1*#3*3/#4", &cards, 72.0, &Rules::default()).is_correct());
+
+        let explanation = explain("a*c*d", &cards, 24.0, &Rules::default());
+        assert_eq!(explanation.missing_cards, vec![3]);
+
+        // Both cards of value 3 are referenced, there is no card left for another 3
+        let explanation = explain("a*b*c*3/d", &cards, 24.0, &Rules::default());
+        assert_eq!(explanation.unknown_numbers, vec![3]);
+
+        assert!(resolve_positions("a*a", &cards, false).is_err());
+        assert!(resolve_positions("a*e", &cards, false).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use super::common::{Card, CardType};
+use super::deck::DeckType;
+use crate::config::config_dir;
+use crate::util::Result;
+
+use lazy_static::lazy_static;
+use serde::{Serialize, Deserialize};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Characters used to draw the built-in cards
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CardCharset {
+    /// ASCII when the terminal doesn't look able to show the Unicode art
+    #[default]
+    Auto,
+    Unicode,
+    Ascii,
+}
+
+impl CardCharset {
+    pub fn is_ascii(&self) -> bool {
+        match self {
+            CardCharset::Unicode => false,
+            CardCharset::Ascii => true,
+            CardCharset::Auto => {
+                // The linux console fonts lack most of the symbols of the cards
+                let console = std::env::var("TERM").map(|term| term == "linux").unwrap_or(false);
+                let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+                    .iter()
+                    .filter_map(|name| std::env::var(name).ok())
+                    .find(|value| !value.is_empty())
+                    .unwrap_or_default()
+                    .to_lowercase();
+                let utf8 = locale.contains("utf-8") || locale.contains("utf8");
+                console || !utf8
+            }
+        }
+    }
+}
+
+/// Size of the art of every card, the same as the built-in cards
+pub const CARD_WIDTH: usize = 14;
+pub const CARD_HEIGHT: usize = 9;
+
+lazy_static! {
+    static ref CARD_PACK: RwLock<Option<CardPack>> = RwLock::new(None);
+}
+
+/// Card art loaded from a directory of text files, one per card (i.e. `sword-12.txt`,
+/// `heart-1.txt`, `clubs-1.txt`, `joker-0.txt`) and `back.txt` for the back of the cards.
+/// The cards without a file keep their built-in art.
+#[derive(Default)]
+pub struct CardPack {
+    fronts: HashMap<Card, String>,
+    back: Option<String>,
+}
+
+impl CardPack {
+    /// Directory of the pack called `name`: `$ConfigDir/termchat/cards/<name>`
+    pub fn dir(name: &str) -> Option<PathBuf> {
+        Some(config_dir()?.join("cards").join(name))
+    }
+
+    /// Loads the valid files of the directory, returning the problems found in the others
+    pub fn load(dir: &Path) -> Result<(CardPack, Vec<String>)> {
+        let cards = DeckType::Spanish50
+            .cards()
+            .into_iter()
+            .chain(DeckType::French52.cards())
+            .flat_map(|card| {
+                let names = Self::file_names(&card);
+                names.into_iter().map(move |name| (name, card))
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut pack = CardPack::default();
+        let mut problems = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let file_name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) if name.ends_with(".txt") => name.to_string(),
+                _ => continue,
+            };
+            let art = match std::fs::read_to_string(&path) {
+                Ok(content) => Self::validate(&content),
+                Err(e) => Err(e.to_string()),
+            };
+            match (art, cards.get(&file_name)) {
+                (Err(problem), _) => problems.push(format!("{file_name}: {problem}")),
+                (Ok(art), _) if file_name == "back.txt" => pack.back = Some(art),
+                (Ok(art), Some(card)) => {
+                    pack.fronts.insert(*card, art);
+                }
+                (Ok(_), None) => problems.push(format!("{file_name}: it is not the name of a card")),
+            }
+        }
+        Ok((pack, problems))
+    }
+
+    /// i.e. `sword-12.txt`. The French clubs are `clubs-<value>.txt`
+    pub fn file_name(card: &Card) -> String {
+        let suit = match card._type {
+            CardType::Clover => String::from("clubs"),
+            suit => format!("{:?}", suit).to_lowercase(),
+        };
+        format!("{}-{}.txt", suit, card.value)
+    }
+
+    /// Names accepted for the file of the card: the French clubs also accept `clover-<value>.txt`
+    fn file_names(card: &Card) -> Vec<String> {
+        match card._type {
+            CardType::Clover => vec![Self::file_name(card), format!("clover-{}.txt", card.value)],
+            _ => vec![Self::file_name(card)],
+        }
+    }
+
+    /// The art must be `CARD_HEIGHT` lines of `CARD_WIDTH` characters
+    fn validate(content: &str) -> std::result::Result<String, String> {
+        let lines = content.lines().collect::<Vec<_>>();
+        let lines = match lines.iter().rposition(|line| !line.trim().is_empty()) {
+            Some(last) => &lines[..=last],
+            None => return Err(String::from("the file is empty")),
+        };
+        if lines.len() != CARD_HEIGHT {
+            return Err(format!("the card has {} lines instead of {}", lines.len(), CARD_HEIGHT))
+        }
+        if let Some((number, line)) =
+            lines.iter().enumerate().find(|(_, line)| line.chars().count() != CARD_WIDTH)
+        {
+            return Err(format!(
+                "the line {} has {} characters instead of {}",
+                number + 1,
+                line.chars().count(),
+                CARD_WIDTH
+            ))
+        }
+        let mut art = lines.join("\n");
+        art.push('\n');
+        Ok(art)
+    }
+
+    /// Files loaded, including the back
+    pub fn file_count(&self) -> usize {
+        self.fronts.len() + self.back.iter().count()
+    }
+
+    /// Replaces the art used to draw the cards
+    pub fn install(self) {
+        *CARD_PACK.write().unwrap() = Some(self);
+    }
+}
+
+/// Art of the card in the installed pack
+pub fn custom_front(card: &Card) -> Option<String> {
+    CARD_PACK.read().unwrap().as_ref()?.fronts.get(card).cloned()
+}
+
+/// Back of the cards in the installed pack
+pub fn custom_back() -> Option<String> {
+    CARD_PACK.read().unwrap().as_ref()?.back.clone()
+}
+
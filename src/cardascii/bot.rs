@@ -0,0 +1,156 @@
+use super::answer::{Expr, Operator};
+use super::common::Card;
+use super::rules::Rules;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How well a bot plays: how long it thinks, how often it doesn't find a solution
+/// and how often it answers without checking its operation
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Skill {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl FromStr for Skill {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Skill, String> {
+        match name {
+            "easy" => Ok(Skill::Easy),
+            "medium" => Ok(Skill::Medium),
+            "hard" => Ok(Skill::Hard),
+            _ => Err(format!("Unknown bot skill '{}', use easy, medium or hard", name)),
+        }
+    }
+}
+
+impl Skill {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Skill::Easy => "easy",
+            Skill::Medium => "medium",
+            Skill::Hard => "hard",
+        }
+    }
+
+    /// Range of seconds needed to solve a hand with many solutions
+    fn think_seconds(&self) -> (f64, f64) {
+        match self {
+            Skill::Easy => (30.0, 60.0),
+            Skill::Medium => (15.0, 30.0),
+            Skill::Hard => (4.0, 10.0),
+        }
+    }
+
+    /// Chance of finding a solution of a hand with many solutions
+    fn insight(&self) -> f64 {
+        match self {
+            Skill::Easy => 0.6,
+            Skill::Medium => 0.85,
+            Skill::Hard => 0.98,
+        }
+    }
+
+    /// Chance of sending a wrong answer instead of thinking more
+    fn carelessness(&self) -> f64 {
+        match self {
+            Skill::Easy => 0.3,
+            Skill::Medium => 0.15,
+            Skill::Hard => 0.03,
+        }
+    }
+}
+
+/// What a bot sends to the table once it has thought about the hand
+#[derive(Clone, Debug)]
+pub enum BotMove {
+    Answer(String),
+    Pass,
+}
+
+/// Solutions looked for to plan the moves of the bots, enough to know how hard a hand is
+pub const SOLUTIONS_LOOKED: usize = 16;
+
+/// Computer player of a table. It is registered in the game like any other player
+/// and its moves are processed by the table as if they were received from the network.
+pub struct Bot {
+    pub name: String,
+    pub skill: Skill,
+    announced: bool,
+    planned_turn: Option<u32>,
+}
+
+impl Bot {
+    pub fn new(name: String, skill: Skill) -> Bot {
+        Bot { name, skill, announced: false, planned_turn: None }
+    }
+
+    /// Whether the other players have been told that the bot joined the table
+    pub fn is_announced(&self) -> bool {
+        self.announced
+    }
+
+    pub fn set_announced(&mut self) {
+        self.announced = true;
+    }
+
+    /// Whether the bot already has a move on the way for the turn
+    pub fn is_planned(&self, turn: u32) -> bool {
+        self.planned_turn == Some(turn)
+    }
+
+    pub fn set_planned(&mut self, turn: Option<u32>) {
+        self.planned_turn = turn;
+    }
+
+    /// Next move of the bot in a turn and the time it takes to make it.
+    /// The hands with few solutions take longer and are passed more often,
+    /// the hands without solution are always passed.
+    pub fn plan(&self, cards: &[Card], solutions: &[Expr], rules: &Rules) -> (Duration, BotMove) {
+        let mut rng = rand::thread_rng();
+        let (min, max) = self.skill.think_seconds();
+        let difficulty = match solutions.len() {
+            0 => 3.0,
+            count => 1.0 + 2.0 / (count as f64).sqrt(),
+        };
+        let delay = Duration::from_secs_f64(rng.gen_range(min..max) * difficulty);
+
+        let bot_move = match solutions.choose(&mut rng) {
+            None => BotMove::Pass,
+            Some(_) if rng.gen_bool(self.skill.carelessness()) => {
+                BotMove::Answer(careless_answer(cards, rules))
+            }
+            Some(solution) if rng.gen_bool(self.skill.insight().powf(difficulty)) => {
+                BotMove::Answer(solution.to_string())
+            }
+            Some(_) => BotMove::Pass,
+        };
+        (delay, bot_move)
+    }
+}
+
+/// The values of the cards in order, joined by random operators
+fn careless_answer(cards: &[Card], rules: &Rules) -> String {
+    let mut rng = rand::thread_rng();
+    let operators = rules
+        .operators
+        .iter()
+        .filter(|operator| !operator.is_unary() && **operator != Operator::Concat)
+        .map(|operator| operator.symbol())
+        .collect::<Vec<_>>();
+    let mut answer = String::new();
+    for (index, card) in cards.iter().enumerate() {
+        if index > 0 {
+            let operator = operators.choose(&mut rng).copied().unwrap_or('+');
+            answer.push_str(&format!(" {operator} "));
+        }
+        answer.push_str(&card.value.to_string());
+    }
+    answer
+}
@@ -0,0 +1,135 @@
+use super::answer::{Expr, Operator};
+use super::common::Card;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Piece {
+    Card(usize), // position of the card in the hand
+    Operator(char),
+}
+
+/// Answer built by picking the cards of the hand and the operators from a palette,
+/// so nobody has to type the values of the cards.
+/// The palette contains the cards of the hand first and then the operators allowed by the
+/// rules and the parentheses. Cards picked one after the other are joined (`1` `2` is `12`),
+/// which is only accepted when the rules allow the concatenation.
+pub struct AnswerBuilder {
+    pieces: Vec<Piece>,
+    selected: usize,
+    operators: Vec<char>,
+}
+
+impl AnswerBuilder {
+    pub fn new(operators: &[Operator]) -> AnswerBuilder {
+        let mut symbols = operators
+            .iter()
+            .filter(|operator| **operator != Operator::Concat)
+            .map(|operator| operator.symbol())
+            .collect::<Vec<_>>();
+        symbols.extend(['(', ')']);
+        AnswerBuilder { pieces: Vec::new(), selected: 0, operators: symbols }
+    }
+
+    /// Operators and parentheses that can be picked after the cards of the hand
+    pub fn operators(&self) -> &Vec<char> {
+        &self.operators
+    }
+    pub fn pieces(&self) -> &Vec<Piece> {
+        &self.pieces
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    pub fn selected(&self, card_count: usize) -> Piece {
+        // The hand could have changed since the selection was made
+        let selected = self.selected % (card_count + self.operators.len());
+        match selected < card_count {
+            true => Piece::Card(selected),
+            false => Piece::Operator(self.operators[selected - card_count]),
+        }
+    }
+
+    pub fn select_next(&mut self, card_count: usize) {
+        self.selected = (self.selected + 1) % (card_count + self.operators.len());
+    }
+
+    pub fn select_previous(&mut self, card_count: usize) {
+        let len = card_count + self.operators.len();
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    pub fn is_used(&self, card: usize) -> bool {
+        self.pieces.contains(&Piece::Card(card))
+    }
+
+    /// Every card of the hand can only be used once
+    pub fn push(&mut self, piece: Piece, card_count: usize) -> Result<(), String> {
+        match piece {
+            Piece::Card(card) if card >= card_count => {
+                return Err(format!("there is no card {} in the hand", card + 1))
+            }
+            Piece::Card(card) if self.is_used(card) => {
+                return Err(format!("the card {} is already used", card + 1))
+            }
+            Piece::Operator(op) if !self.operators.contains(&op) => {
+                return Err(format!("'{op}' is not an allowed operator"))
+            }
+            _ => (),
+        }
+        self.pieces.push(piece);
+        Ok(())
+    }
+
+    pub fn push_selected(&mut self, card_count: usize) -> Result<(), String> {
+        self.push(self.selected(card_count), card_count)
+    }
+
+    pub fn pop(&mut self) {
+        self.pieces.pop();
+    }
+
+    pub fn clear(&mut self) {
+        self.pieces.clear();
+    }
+
+    /// Expression with the real values of the cards, i.e. `(7 + 5) * 2`
+    pub fn expression(&self, cards: &[Card]) -> String {
+        Self::write(&self.pieces, cards)
+    }
+
+    /// Value of the expression built so far.
+    /// An incomplete expression is evaluated as if the last operators were not there
+    /// and the open parentheses were closed.
+    pub fn running_value(&self, cards: &[Card]) -> Option<f64> {
+        let mut end = self.pieces.len();
+        while end > 0 && matches!(self.pieces[end - 1], Piece::Operator(op) if op != ')' && op != '!') {
+            end -= 1;
+        }
+        if end == 0 {
+            return None
+        }
+        let pieces = &self.pieces[..end];
+        let opened = pieces.iter().filter(|piece| **piece == Piece::Operator('(')).count();
+        let closed = pieces.iter().filter(|piece| **piece == Piece::Operator(')')).count();
+        let mut expression = Self::write(pieces, cards);
+        expression.push_str(&")".repeat(opened.saturating_sub(closed)));
+
+        Expr::parse(&expression).and_then(|expr| expr.value()).ok()
+    }
+
+    fn write(pieces: &[Piece], cards: &[Card]) -> String {
+        let mut out = String::new();
+        for piece in pieces {
+            match piece {
+                Piece::Card(card) => match cards.get(*card) {
+                    Some(card) => out.push_str(&card.value.to_string()),
+                    None => out.push('?'),
+                },
+                Piece::Operator(op @ ('(' | ')' | '√' | '!')) => out.push(*op),
+                Piece::Operator(op) => out.push_str(&format!(" {op} ")),
+            }
+        }
+        out
+    }
+}
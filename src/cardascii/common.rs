@@ -10,7 +10,12 @@ pub enum CardType {
     Club,
     Sword,
     Cup,
-    Joker
+    Joker,
+    // French suits, `Clover` are the clubs
+    Spade,
+    Heart,
+    Diamond,
+    Clover,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Debug)]
@@ -22,8 +27,51 @@ pub struct Card {
 pub const BYTECOUNT: usize = 32;
 pub type AnswerData = [char; BYTECOUNT];
 
-pub(crate) const CARDCOUNT: usize = 4;
-pub type HandCardData = [Card; CARDCOUNT];
+/// Cards of a turn, as many as the rules of the table say
+pub type HandCardData = Vec<Card>;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum TurnOutcome {
+    Winner(String, String), // user, answer
+    Tie,
+}
+
+/// State of the game during a turn, shown in the cards panel
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct TurnStatus {
+    pub turn: u32,
+    /// Cards not dealt yet
+    pub deck_left: usize,
+    /// Cards of the tied turns, for the next winner
+    pub pot: usize,
+    /// Players that passed this turn
+    pub passed: usize,
+}
+
+impl std::fmt::Display for TurnStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Turn {} • {} left in deck • pot {} cards • {} passed",
+            self.turn, self.deck_left, self.pot, self.passed
+        )
+    }
+}
+
+/// How a turn ended, sent by the table to every player
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TurnRecord {
+    pub turn: u32,
+    pub timestamp: i64,
+    pub cards: Vec<Card>,
+    /// Card whose value was the target of the turn (Krypto variant)
+    pub target_card: Option<Card>,
+    pub outcome: TurnOutcome,
+    /// Distinct solutions of the hand, counted by the table when the turn ends
+    pub solutions: usize,
+    /// Whether the search of solutions was complete, otherwise the hand had at least `solutions`
+    pub solutions_complete: bool,
+}
 
 
 #[derive(Serialize, Deserialize)]
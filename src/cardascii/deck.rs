@@ -0,0 +1,53 @@
+use serde::{Serialize, Deserialize};
+use super::common::{Card, CardType};
+
+/// Decks that a table can play with, chosen in the rules
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DeckType {
+    /// Spanish deck without the 8s and 9s
+    Spanish40,
+    Spanish48,
+    /// Spanish deck with two jokers valued 0
+    #[default]
+    Spanish50,
+    /// French deck, the J, Q and K are valued 11, 12 and 13
+    French52,
+}
+
+impl DeckType {
+    pub const SPANISH_SUITS: [CardType; 4] =
+        [CardType::Sword, CardType::Club, CardType::Gold, CardType::Cup];
+    pub const FRENCH_SUITS: [CardType; 4] =
+        [CardType::Spade, CardType::Heart, CardType::Diamond, CardType::Clover];
+
+    pub fn cards(&self) -> Vec<Card> {
+        let mut cards = Vec::new();
+        if *self == DeckType::Spanish50 {
+            cards.push(Card { _type: CardType::Joker, value: 0 });
+            cards.push(Card { _type: CardType::Joker, value: 0 });
+        }
+        let (suits, values) = match self {
+            DeckType::Spanish40 => (Self::SPANISH_SUITS, vec![12, 11, 10, 7, 6, 5, 4, 3, 2, 1]),
+            DeckType::Spanish48 | DeckType::Spanish50 => (Self::SPANISH_SUITS, (1..=12).rev().collect()),
+            DeckType::French52 => (Self::FRENCH_SUITS, (1..=13).rev().collect()),
+        };
+        for _type in suits {
+            cards.extend(values.iter().map(|value| Card { _type, value: *value }));
+        }
+        cards
+    }
+
+    /// i.e. "the Spanish deck of 40 cards"
+    pub fn description(&self) -> String {
+        let name = match self {
+            DeckType::French52 => "French",
+            _ => "Spanish",
+        };
+        let jokers = match self {
+            DeckType::Spanish50 => " with jokers",
+            _ => "",
+        };
+        format!("the {} deck of {} cards{}", name, self.cards().len(), jokers)
+    }
+}
@@ -1,7 +1,10 @@
 use rand::prelude::SliceRandom;
-use rand::thread_rng;
-use rcalc::{Interpreter, Lexer, Token};
-use super::{common::{Card, CardType}};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use super::common::{Card, TurnStatus};
+use super::deck::DeckType;
+use super::answer::{explain, parse_answer, AnswerExplanation, Expr};
+use super::rules::Rules;
 use bimap::BiMap;
 use std::collections::HashMap;
 /*const CARD_ID_JOCKER_1: u8 = 0;
@@ -11,24 +14,18 @@ const CARD_ID_JOCKER_2: u8 = 1;
 #[derive(Debug)]
 pub struct Game24Err(pub String);
 
+/// Random generator that shuffles the deck of a game
+pub type GameRng = Box<dyn RngCore + Send>;
+
 struct Deck{
     cards: Vec<Card>
 }
 
 impl Deck {
-    fn new() -> Self {
-
-        let mut me = Deck {
-            cards : Vec::<Card>::new(),
-        };
-
-        load_cards(& mut me);
-
-        me
-    }
-
-    fn add(&mut self, _type: CardType, value: u8) {
-        self.cards.push( Card{ _type, value } );
+    fn new(deck_type: DeckType) -> Self {
+        Deck {
+            cards : deck_type.cards(),
+        }
     }
 
     /*fn as_ids(& self) -> Vec<u8> {
@@ -103,12 +100,12 @@ impl CardStack {
         result
     }
 
-    fn shuffle(&mut self) {
-        self.card_ids.shuffle(&mut thread_rng());
+    fn shuffle(&mut self, rng: &mut GameRng) {
+        self.card_ids.shuffle(rng);
     }
 
-    fn is_empty(&self) -> bool {
-        self.card_ids.is_empty()
+    fn len(&self) -> usize {
+        self.card_ids.len()
     }
 
 }
@@ -122,10 +119,32 @@ pub enum TurnResult {
     Abandoned
 }
 
+/// An answer made by a player during a turn
+#[derive(Clone)]
+pub struct Attempt {
+    pub user:       String,
+    pub answer:     String,
+    pub timestamp:  i64,
+    pub error:      Option<String>,
+}
+
 pub struct Turn {
-    num:            u8,
+    num:            u32,
     pub visible_cards:  CardStack,
-    pub result:         TurnResult
+    /// Only used by the Krypto variant of the rules
+    pub target_card:    CardStack,
+    pub result:         TurnResult,
+    attempts:       Vec<Attempt>,
+}
+
+impl Turn {
+    pub fn num(&self) -> u32 {
+        self.num
+    }
+
+    pub fn attempts(&self) -> &Vec<Attempt> {
+        &self.attempts
+    }
 }
 
 pub struct Game24{
@@ -136,19 +155,32 @@ pub struct Game24{
     hidden_cards:       CardStack,
     players_cards:      Vec<CardStack>,
     accumulate_cards:   CardStack,
-    turn:               Turn
+    rules:              Rules,
+    turn:               Turn,
+    seed:               u64,
+    rng:                GameRng,
 }
 
 impl Game24 {
-    pub(crate) fn new(/*player: u8, deck: &'a Deck*/) -> Self {
+    pub(crate) fn new(rules: Rules) -> Self {
+        Self::with_seed(rules, rand::random())
+    }
+
+    /// The order of the cards only depends on the seed and the deck of the rules,
+    /// the games with the same seed deal the same hands
+    pub(crate) fn with_seed(rules: Rules, seed: u64) -> Self {
+        Self::with_rng::<ChaCha8Rng>(rules, seed)
+    }
+
+    /// Game whose deck is shuffled by a `R` generator created from `seed`,
+    /// so the seed always replays the game or deals the same cards in other tables.
+    pub(crate) fn with_rng<R: SeedableRng + RngCore + Send + 'static>(rules: Rules, seed: u64) -> Self {
+        let mut rng: GameRng = Box::new(R::seed_from_u64(seed));
         let mut hidden_cards = CardStack::new(false);
-        let deck = Deck::new();
+        let deck = Deck::new(rules.deck);
         hidden_cards.add_cards(&deck);
-        hidden_cards.shuffle();
-        let mut players_cards = Vec::<CardStack>::new();
-
-        players_cards.push(CardStack::new(false));
-        players_cards.push(CardStack::new(false));
+        hidden_cards.shuffle(&mut rng);
+        let players_cards = Vec::<CardStack>::new();
 
         Game24 {
             player_count: 0,
@@ -158,34 +190,108 @@ impl Game24 {
             hidden_cards,
             players_cards,
             accumulate_cards: CardStack::new(false),
+            rules,
             turn: Turn {
                 num: 0,
                 visible_cards: CardStack::new(true),
-                result: TurnResult::Gaming
-            }
+                target_card: CardStack::new(true),
+                result: TurnResult::Gaming,
+                attempts: Vec::new(),
+            },
+            seed,
+            rng,
         }
     }
 
+    /// Takes back all the cards to the deck and shuffles it
     pub fn do_reset(&mut self) {
         self.hidden_cards.add_all_from( &mut self.turn.visible_cards );
+        self.hidden_cards.add_all_from( &mut self.turn.target_card );
+        self.hidden_cards.add_all_from( &mut self.accumulate_cards );
         for player_card in & mut self.players_cards.iter_mut() {
             self.hidden_cards.add_all_from( player_card );
         }
         
-        for player in self.players.right_values().enumerate() {
-            self.players_gaming_turn.insert(player.0, false);
+        for gaming_turn in self.players_gaming_turn.values_mut() {
+            *gaming_turn = false;
         }
 
-        self.hidden_cards.shuffle();
+        self.hidden_cards.shuffle(&mut self.rng);
     }
 
     pub fn do_give_cards(&mut self) -> Result< & Turn , Game24Err>{
-        if ! self.hidden_cards.is_empty() && self.turn.visible_cards.add_n_from(&mut self.hidden_cards, 4) {
-            self.turn.num += 1;
-            self.turn.result = TurnResult::Gaming;
-            return Ok( & self.turn );
+        let count = self.rules.cards as u8;
+        // No card is moved unless the whole hand (and its target card) can be dealt
+        let needed = self.rules.cards + usize::from(self.rules.target_card);
+        if self.hidden_cards.len() < needed {
+            return Err(Game24Err(String::from("we can't do cards")))
+        }
+        self.turn.visible_cards.add_n_from(&mut self.hidden_cards, count);
+        if self.rules.target_card {
+            self.turn.target_card.add_one_from(&mut self.hidden_cards);
+        }
+        self.turn.num += 1;
+        self.turn.result = TurnResult::Gaming;
+        self.turn.attempts.clear();
+        for gaming_turn in self.players_gaming_turn.values_mut() {
+            *gaming_turn = true;
         }
-        Err(Game24Err(format!("we can't do cards")))
+        Ok( & self.turn )
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Card whose value is the target of the turn in the Krypto variant
+    pub fn target_card(&self) -> Option<&Card> {
+        self.deck.get_cards_from_stack(& self.turn.target_card).into_iter().next()
+    }
+
+    /// Value that the answers of the turn must reach
+    pub fn target(&self) -> f64 {
+        match self.target_card() {
+            Some(card) => f64::from(card.value),
+            None => self.rules.target_value(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn turn_num(&self) -> u32 {
+        self.turn.num
+    }
+
+    pub fn status(&self) -> TurnStatus {
+        let passed = match self.turn.result {
+            TurnResult::Gaming => self.players_gaming_turn.values().filter(|gaming| !**gaming).count(),
+            _ => 0,
+        };
+        TurnStatus {
+            turn: self.turn.num,
+            deck_left: self.hidden_cards.card_ids.len(),
+            pot: self.accumulate_cards.card_ids.len(),
+            passed,
+        }
+    }
+
+    pub fn attempts(&self) -> &Vec<Attempt> {
+        self.turn.attempts()
+    }
+
+    /// Every answer, pass (and future hints) is tagged with the turn it was made for,
+    /// anything sent for another turn arrived too late.
+    pub fn check_turn(&self, turn: u32) -> Result< (), Game24Err > {
+        if turn != self.turn.num {
+            return Err(Game24Err(format!(
+                "too late: it was for turn {turn}, the current turn is {}", self.turn.num)))
+        }
+        if self.turn.result != TurnResult::Gaming {
+            return Err(Game24Err(format!("too late: turn {turn} has already finished")))
+        }
+        Ok(())
     }
 
     pub fn get_gived_cards(&self) -> Vec<&Card> {
@@ -195,15 +301,42 @@ impl Game24 {
     pub fn do_user_registration(& mut self, user: & String) -> Result< (), Game24Err > {
         match self.players.get_by_left(user) {
             None => {
+                // The player id is the position of its won cards in `players_cards`
+                let id = self.player_count;
                 self.player_count += 1;
-                self.players.insert(user.clone(), self.player_count);
+                self.players.insert(user.clone(), id);
+                self.players_cards.push(CardStack::new(false));
+                self.players_gaming_turn.insert(id, true);
+                Ok(())
+            },
+            // A player that left the table is back, with the cards it won
+            Some(id) if !self.players_gaming_turn.contains_key(id) => {
+                self.players_gaming_turn.insert(*id, true);
                 Ok(())
             },
             Some(_) => Err(Game24Err(format!("the user already exists")))
         }
     }
 
-    pub fn do_pass(&mut self, user: & String) -> Result< & Turn, Game24Err >{
+    /// The player doesn't play the next turns until it registers again, so the turns
+    /// don't wait for it. Returns whether the turn ended because the others had passed.
+    pub fn do_user_leave(&mut self, user: & String) -> Result< bool, Game24Err > {
+        let id = match self.players.get_by_left(user) {
+            Some(id) => *id,
+            None => return Err(Game24Err(String::from("User not registered"))),
+        };
+        self.players_gaming_turn.remove(&id);
+        let everybody_passed = !self.players_gaming_turn.is_empty()
+            && !self.players_gaming_turn.values().any(|&gaming| gaming);
+        if self.turn.result == TurnResult::Gaming && everybody_passed {
+            self.end_turn(TurnResult::Tie);
+            return Ok(true)
+        }
+        Ok(false)
+    }
+
+    pub fn do_pass(&mut self, user: & String, turn: u32) -> Result< & Turn, Game24Err >{
+        self.check_turn(turn)?;
         match self.players.get_by_left(user).cloned() {
             Some(user) =>  {
                 match self.players_gaming_turn.get_mut( &user ) {
@@ -221,15 +354,41 @@ impl Game24 {
         }
     }
 
-    pub fn make_answer(&mut self, user: & String, answer: String) -> Result< & Turn, Game24Err > {
-        let result_24 = self.validate_24_result(user, & answer);
-        match result_24 {
-            Ok(()) => match self.validate_card_usage_answer(user, & answer) {
-                Ok(()) => Ok(& self.turn),
-                Err(e) => Err(e)
-            },
-            Err(e) => Err(e)
+    /// Checks the answer of a player, the first correct answer wins the turn.
+    /// Returns the accepted solution in its canonical form.
+    pub fn make_answer(&mut self, user: & String, turn: u32, answer: String) -> Result< Expr, Box<AnswerExplanation> > {
+        if let Err(Game24Err(msg)) = self.check_turn(turn) {
+            return Err(Box::new(AnswerExplanation::rejected(& answer, msg)))
         }
+        let id = match self.players.get_by_left(user).cloned() {
+            Some(id) => id,
+            None => {
+                let problem = String::from("User not registered");
+                return Err(Box::new(AnswerExplanation::rejected(& answer, problem)))
+            }
+        };
+        let cards = self.get_gived_cards().into_iter().cloned().collect::<Vec<_>>();
+        let explanation = explain(& answer, & cards, self.target(), & self.rules);
+        let expression = explanation.expression.clone().unwrap_or_else(|| answer.clone());
+
+        // The turn only ends once the answer is known to be a valid solution
+        let result = match explanation.is_correct() {
+            true => parse_answer(& answer, & cards, & self.rules)
+                .map(|expr| expr.canonical())
+                .map_err(|problem| Box::new(AnswerExplanation::rejected(& answer, problem))),
+            false => Err(Box::new(explanation)),
+        };
+
+        self.turn.attempts.push(Attempt {
+            user: user.clone(),
+            answer: expression,
+            timestamp: chrono::Local::now().timestamp(),
+            error: result.as_ref().err().and_then(|explanation| explanation.problem.clone()),
+        });
+        if result.is_ok() {
+            self.end_turn(TurnResult::Winner(id));
+        }
+        result
     }
 
     fn end_turn(&mut self, result: TurnResult) {
@@ -238,128 +397,15 @@ impl Game24 {
             TurnResult::Winner(user) => {
                 self.players_cards[user].add_all_from(&mut self.accumulate_cards);
                 self.players_cards[user].add_all_from(&mut self.turn.visible_cards);
+                self.players_cards[user].add_all_from(&mut self.turn.target_card);
+            }
+            TurnResult::Tie => {
+                self.accumulate_cards.add_all_from(&mut self.turn.visible_cards);
+                self.accumulate_cards.add_all_from(&mut self.turn.target_card);
             }
-            TurnResult::Tie =>
-                self.accumulate_cards.add_all_from(&mut self.turn.visible_cards),
             _ => ()
         }
         self.turn.result = result;
     }
 
-    fn validate_24_result(&mut self, user: & String, answer: & String) -> Result< (), Game24Err > {
-        match self.players.get_by_left(user) {
-            Some(_) =>  {
-                let mut program = Interpreter::from(answer.as_str());
-
-                match program.interpret() {
-                    Ok(result) => { 
-                        if result == 24.0 {
-                            Ok(())
-                        } else {
-                            Err(Game24Err(format!("the result of your operation isn't 24 result is {result}")))
-                        }
-                    },
-                    Err(e) =>
-                        Err(Game24Err(format!("the operation isn't correct\n{e}")))
-                }
-            },
-            None => Err(Game24Err(format!("User not registered")))
-        }
-    }
-
-    fn validate_card_usage_answer(&mut self, user: & String, answer: & String) -> Result< (), Game24Err > {
-        let mut lexer = Lexer::from(answer.as_str());
-
-        let mut cards_vec   = self.deck.get_cards_from_stack(& mut self.turn.visible_cards);
-
-        while let Ok(token) =  lexer.next_token() {
-            if token == Token::EOF {
-                break;
-            }
-            if let Token::NUMBER(n)  = token {
-                let pos = cards_vec
-                    .iter()
-                    .position( |x| usize::from(x.value) == n );
-
-                if let Some(pos) = pos {
-                    cards_vec.remove(pos);
-                } else {
-                    return Err(Game24Err(format!("you are using numbers that don't exist in this cards {n}")));
-                }
-            }
-        }
-        if cards_vec.is_empty() {
-            match self.players.get_by_left(user).cloned() {
-                Some(user) =>  {
-                    self.end_turn(TurnResult::Winner(user));
-                    Ok(())
-                },
-                None => Err(Game24Err(format!("User not registered")))
-            }
-
-        } else {
-            Err(Game24Err(format!("don't use this cards {cards_vec:?}")))
-        }
-        
-    }
-
-}
-
-fn load_cards(deck: & mut Deck) {
-
-    deck.add(CardType::Joker, 0);
-    deck.add(CardType::Joker, 0);
-
-    deck.add(CardType::Sword, 12);
-    deck.add(CardType::Sword, 11);
-    deck.add(CardType::Sword, 10);
-    deck.add(CardType::Sword, 9);
-    deck.add(CardType::Sword, 8);
-    deck.add(CardType::Sword, 7);
-    deck.add(CardType::Sword, 6);
-    deck.add(CardType::Sword, 5);
-    deck.add(CardType::Sword, 4);
-    deck.add(CardType::Sword, 3);
-    deck.add(CardType::Sword, 2);
-    deck.add(CardType::Sword, 1);
-
-    deck.add(CardType::Club, 12);
-    deck.add(CardType::Club, 11);
-    deck.add(CardType::Club, 10);
-    deck.add(CardType::Club, 9);
-    deck.add(CardType::Club, 8);
-    deck.add(CardType::Club, 7);
-    deck.add(CardType::Club, 6);
-    deck.add(CardType::Club, 5);
-    deck.add(CardType::Club, 4);
-    deck.add(CardType::Club, 3);
-    deck.add(CardType::Club, 2);
-    deck.add(CardType::Club, 1);
-
-    deck.add(CardType::Gold, 12);
-    deck.add(CardType::Gold, 11);
-    deck.add(CardType::Gold, 10);
-    deck.add(CardType::Gold, 9);
-    deck.add(CardType::Gold, 8);
-    deck.add(CardType::Gold, 7);
-    deck.add(CardType::Gold, 6);
-    deck.add(CardType::Gold, 5);
-    deck.add(CardType::Gold, 4);
-    deck.add(CardType::Gold, 3);
-    deck.add(CardType::Gold, 2);
-    deck.add(CardType::Gold, 1);
-
-    deck.add(CardType::Cup, 12);
-    deck.add(CardType::Cup, 11);
-    deck.add(CardType::Cup, 10);
-    deck.add(CardType::Cup, 9);
-    deck.add(CardType::Cup, 8);
-    deck.add(CardType::Cup, 7);
-    deck.add(CardType::Cup, 6);
-    deck.add(CardType::Cup, 5);
-    deck.add(CardType::Cup, 4);
-    deck.add(CardType::Cup, 3);
-    deck.add(CardType::Cup, 2);
-    deck.add(CardType::Cup, 1);
-
 }
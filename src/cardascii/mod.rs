@@ -1,4 +1,11 @@
 pub mod common;
 pub mod terminal;
 pub mod game;
-
+pub mod builder;
+pub mod answer;
+pub mod solver;
+pub mod bot;
+pub mod solo;
+pub mod rules;
+pub mod deck;
+pub mod art;
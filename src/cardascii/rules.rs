@@ -0,0 +1,94 @@
+use serde::{Serialize, Deserialize};
+use super::answer::Operator;
+use super::deck::DeckType;
+
+/// Rules of the game played in a table.
+/// They are chosen in the config of the table and sent to the players when they join.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Rules {
+    /// Value that the operation of an answer must reach
+    pub target: u32,
+    /// Cards dealt each turn. The solutions of the hands with more than 4 cards
+    /// are only counted up to the limits of the solver
+    pub cards: usize,
+    /// Operators allowed in the answers
+    pub operators: Vec<Operator>,
+    /// The answers are only revealed to the other players when the turn ends
+    pub sealed_answers: bool,
+    /// Krypto variant: each turn a target card is dealt with the hand,
+    /// its value is the target instead of `target`
+    pub target_card: bool,
+    /// Deck the cards are dealt from
+    pub deck: DeckType,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            target: 24,
+            cards: 4,
+            operators: vec![Operator::Add, Operator::Sub, Operator::Mul, Operator::Div],
+            sealed_answers: false,
+            target_card: false,
+            deck: DeckType::default(),
+        }
+    }
+}
+
+impl Rules {
+    pub const MIN_CARDS: usize = 3;
+    pub const MAX_CARDS: usize = 6;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(Self::MIN_CARDS..=Self::MAX_CARDS).contains(&self.cards) {
+            return Err(format!(
+                "a hand must have from {} to {} cards, not {}",
+                Self::MIN_CARDS,
+                Self::MAX_CARDS,
+                self.cards
+            ))
+        }
+        if self.operators.iter().all(Operator::is_unary) {
+            return Err(String::from("at least one operator that joins two values must be allowed"))
+        }
+        Ok(())
+    }
+
+    pub fn target_value(&self) -> f64 {
+        f64::from(self.target)
+    }
+
+    pub fn allows(&self, operator: Operator) -> bool {
+        self.operators.contains(&operator)
+    }
+
+    /// i.e. "make 24 with 4 cards using + - * /"
+    pub fn description(&self) -> String {
+        let operators = self
+            .operators
+            .iter()
+            .filter(|operator| **operator != Operator::Concat)
+            .map(|operator| operator.symbol().to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let target = match self.target_card {
+            true => String::from("the value of the target card"),
+            false => self.target.to_string(),
+        };
+        let mut description = format!(
+            "make {} with {} cards of {} using {}",
+            target,
+            self.cards,
+            self.deck.description(),
+            operators
+        );
+        if self.allows(Operator::Concat) {
+            description.push_str(", the cards can be joined (12 with 1 and 2)");
+        }
+        if self.sealed_answers {
+            description.push_str(", the answers are sealed until the turn ends");
+        }
+        description
+    }
+}
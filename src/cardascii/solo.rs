@@ -0,0 +1,173 @@
+use chrono::{Datelike, NaiveDate};
+
+use std::time::{Duration, Instant};
+
+/// Hands of the daily puzzle
+pub const DAILY_HANDS: usize = 10;
+
+/// Seed of the daily puzzle of a date, i.e. 20261019, the same for everyone
+pub fn daily_seed(date: NaiveDate) -> u64 {
+    date.year() as u64 * 10000 + u64::from(date.month()) * 100 + u64::from(date.day())
+}
+
+/// Move of the player in solo mode, checked by the local game instead of a table
+#[derive(Clone, Debug)]
+pub enum SoloMove {
+    Answer(u32, String),
+    Skip,
+    Reveal,
+}
+
+/// How a hand of the session ended
+#[derive(Clone, Copy, Debug)]
+pub enum HandResult {
+    /// Solved after a number of wrong answers
+    Solved(usize),
+    Skipped,
+    Revealed,
+}
+
+impl HandResult {
+    /// Square of the hand in the result line
+    fn symbol(&self) -> char {
+        match self {
+            HandResult::Solved(0) => '🟩',
+            HandResult::Solved(_) => '🟨',
+            HandResult::Skipped | HandResult::Revealed => '⬛',
+        }
+    }
+}
+
+/// Offline practice: the moves waiting to be checked and the personal stats of the session
+pub struct Solo {
+    moves: Vec<SoloMove>,
+    /// Seed of the deck, the sessions with the same seed get the same hands
+    seed: u64,
+    /// Date of the daily puzzle, that only has `DAILY_HANDS` hands
+    daily: Option<NaiveDate>,
+    hands: Vec<HandResult>,
+    hand_misses: usize,
+    hand_start: Instant,
+    solve_times: Vec<Duration>,
+    streak: usize,
+    best_streak: usize,
+    misses: usize,
+    skips: usize,
+    reveals: usize,
+}
+
+impl Solo {
+    pub fn new(seed: u64, daily: Option<NaiveDate>) -> Solo {
+        Solo {
+            moves: Vec::new(),
+            seed,
+            daily,
+            hands: Vec::new(),
+            hand_misses: 0,
+            hand_start: Instant::now(),
+            solve_times: Vec::new(),
+            streak: 0,
+            best_streak: 0,
+            misses: 0,
+            skips: 0,
+            reveals: 0,
+        }
+    }
+
+    pub fn push_move(&mut self, solo_move: SoloMove) {
+        self.moves.push(solo_move);
+    }
+
+    pub fn take_moves(&mut self) -> Vec<SoloMove> {
+        std::mem::take(&mut self.moves)
+    }
+
+    /// The time to solve a hand is counted since it is dealt
+    pub fn start_hand(&mut self) {
+        self.hand_start = Instant::now();
+        self.hand_misses = 0;
+    }
+
+    pub fn hand_time(&self) -> Duration {
+        self.hand_start.elapsed()
+    }
+
+    pub fn streak(&self) -> usize {
+        self.streak
+    }
+
+    pub fn is_daily(&self) -> bool {
+        self.daily.is_some()
+    }
+
+    /// The daily puzzle ends after its hands, the practice never ends
+    pub fn is_finished(&self) -> bool {
+        self.daily.is_some() && self.hands.len() >= DAILY_HANDS
+    }
+
+    pub fn solved(&mut self, time: Duration) {
+        self.hands.push(HandResult::Solved(self.hand_misses));
+        self.solve_times.push(time);
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+    }
+
+    /// A wrong answer breaks the streak, the hand can still be solved
+    pub fn missed(&mut self) {
+        self.misses += 1;
+        self.hand_misses += 1;
+        self.streak = 0;
+    }
+
+    pub fn skipped(&mut self) {
+        self.hands.push(HandResult::Skipped);
+        self.skips += 1;
+        self.streak = 0;
+    }
+
+    pub fn revealed(&mut self) {
+        self.hands.push(HandResult::Revealed);
+        self.reveals += 1;
+        self.streak = 0;
+    }
+
+    /// i.e. "5 solved (average 21.4s, best 6.2s) • streak 2 (best 4) • 3 missed • 1 skipped • 0 revealed"
+    pub fn summary(&self) -> String {
+        let solved = match self.solve_times.iter().min() {
+            Some(best) => {
+                let total = self.solve_times.iter().sum::<Duration>();
+                format!(
+                    "{} solved (average {:.1}s, best {:.1}s)",
+                    self.solve_times.len(),
+                    total.as_secs_f64() / self.solve_times.len() as f64,
+                    best.as_secs_f64()
+                )
+            }
+            None => String::from("0 solved"),
+        };
+        format!(
+            "{} • streak {} (best {}) • {} missed • {} skipped • {} revealed",
+            solved, self.streak, self.best_streak, self.misses, self.skips, self.reveals
+        )
+    }
+
+    /// Line to share the result of the session and compare it with the other players
+    /// of the same seed, i.e. "termchat daily 2026-10-19 • 8/10 solved in 3m12s • 🟩🟩🟨⬛🟩🟩🟩🟨⬛🟩"
+    pub fn result_line(&self) -> String {
+        let puzzle = match self.daily {
+            Some(date) => format!("termchat daily {}", date.format("%Y-%m-%d")),
+            None => format!("termchat solo seed {}", self.seed),
+        };
+        let total = self.solve_times.iter().sum::<Duration>().as_secs();
+        let hands = self.hands.iter().map(HandResult::symbol).collect::<String>();
+        format!(
+            "{} • {}/{} solved in {}m{:02}s • {}",
+            puzzle,
+            self.solve_times.len(),
+            self.hands.len(),
+            total / 60,
+            total % 60,
+            hands
+        )
+    }
+}
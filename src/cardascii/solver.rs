@@ -0,0 +1,210 @@
+use super::answer::{Expr, Operator, MAX_FACTORIAL};
+use super::rules::Rules;
+
+use std::collections::HashSet;
+
+/// Most combinations tried by a search, some tens of milliseconds. The search runs while the
+/// table processes the moves, so a hand with many cards or operators can't keep it busy.
+/// A hand of 4 cards with the basic operators needs less than 5000.
+pub const MAX_STEPS: usize = 50_000;
+
+/// Most solutions counted at the end of a turn
+pub const MAX_SOLUTIONS: usize = 1000;
+
+/// Solutions found by a search
+pub struct Solutions {
+    pub found: Vec<Expr>,
+    /// Whether every combination was tried, otherwise the hand can have more solutions
+    pub complete: bool,
+}
+
+impl Solutions {
+    /// i.e. "3 distinct solutions" or "at least 100 distinct solutions"
+    pub fn count_text(&self) -> String {
+        count_text(self.found.len(), self.complete)
+    }
+}
+
+pub fn count_text(count: usize, complete: bool) -> String {
+    match complete {
+        true => format!("{} distinct solutions", count),
+        false => format!("at least {} distinct solutions", count),
+    }
+}
+
+/// Distinct solutions (in canonical form) that reach the target
+/// using all the values once and only the operators allowed by the rules.
+/// The search stops after `limit` solutions or `MAX_STEPS` combinations.
+pub fn solutions(values: &[u8], target: f64, rules: &Rules, limit: usize) -> Solutions {
+    let operands = values
+        .iter()
+        .map(|value| (Expr::Number(usize::from(*value)), f64::from(*value)))
+        .collect::<Vec<_>>();
+
+    let mut search = Search { target, rules, limit, steps: 0, seen: HashSet::new(), found: Vec::new() };
+    let mut complete = true;
+    for operands in with_unary_operators(operands, rules) {
+        if !search.run(operands) {
+            complete = false;
+            break
+        }
+    }
+    Solutions { found: search.found, complete }
+}
+
+/// Every combination of the operands with and without the unary operators applied.
+/// To keep the search finite, an operand only gets one unary operator
+/// and only when it changes its value to a whole number: `√9`, `3!`, but not `√2` or `2!`.
+fn with_unary_operators(operands: Vec<(Expr, f64)>, rules: &Rules) -> Vec<Vec<(Expr, f64)>> {
+    operands.into_iter().fold(vec![Vec::new()], |combinations, operand| {
+        let variants = unary_variants(operand, rules);
+        combinations
+            .iter()
+            .flat_map(|combination| {
+                variants.iter().map(move |variant| {
+                    let mut combination = combination.clone();
+                    combination.push(variant.clone());
+                    combination
+                })
+            })
+            .collect()
+    })
+}
+
+fn unary_variants((expr, value): (Expr, f64), rules: &Rules) -> Vec<(Expr, f64)> {
+    let mut variants = Vec::new();
+    for operator in rules.operators.iter().copied().filter(Operator::is_unary) {
+        let useful = match operator {
+            Operator::Sqrt => value > 1.0 && value.sqrt().fract() == 0.0,
+            _ => value > 2.0 && value <= MAX_FACTORIAL && value.fract() == 0.0,
+        };
+        if let (true, Ok(result)) = (useful, operator.apply_unary(value)) {
+            variants.push((Expr::Unary(operator, Box::new(expr.clone())), result));
+        }
+    }
+    variants.insert(0, (expr, value));
+    variants
+}
+
+struct Search<'a> {
+    target: f64,
+    rules: &'a Rules,
+    limit: usize,
+    steps: usize,
+    seen: HashSet<String>,
+    found: Vec<Expr>,
+}
+
+impl Search<'_> {
+    /// Combines every pair of operands with every operator until only one operand remains.
+    /// Returns false when the search is stopped by its limits.
+    fn run(&mut self, operands: Vec<(Expr, f64)>) -> bool {
+        self.steps += 1;
+        if self.steps > MAX_STEPS || self.found.len() >= self.limit {
+            return false
+        }
+        if operands.len() == 1 {
+            let (expr, value) = &operands[0];
+            if (value - self.target).abs() < 1e-9 {
+                let solution = expr.canonical();
+                if self.seen.insert(solution.to_string()) {
+                    self.found.push(solution);
+                }
+            }
+            return true
+        }
+
+        let rules = self.rules;
+        for i in 0..operands.len() {
+            for j in 0..operands.len() {
+                if i == j {
+                    continue
+                }
+                let (left, a) = &operands[i];
+                let (right, b) = &operands[j];
+                for operator in rules.operators.iter().copied().filter(|operator| !operator.is_unary()) {
+                    match operator {
+                        // Both orders give the same result
+                        Operator::Add | Operator::Mul if i > j => continue,
+                        // Only the values of the cards can be joined
+                        Operator::Concat if !matches!((left, right), (Expr::Number(_), Expr::Number(_))) => {
+                            continue
+                        }
+                        _ => (),
+                    }
+                    let (expr, value) = match operator.apply(*a, *b) {
+                        Ok(value) if operator == Operator::Concat => (Expr::Number(value as usize), value),
+                        Ok(value) => {
+                            (Expr::Binary(operator, Box::new(left.clone()), Box::new(right.clone())), value)
+                        }
+                        Err(_) => continue,
+                    };
+                    let rest = operands
+                        .iter()
+                        .enumerate()
+                        .filter(|(k, _)| *k != i && *k != j)
+                        .map(|(_, operand)| operand.clone())
+                        .collect::<Vec<_>>();
+                    for operand in unary_variants((expr, value), rules) {
+                        let mut next = rest.clone();
+                        next.push(operand);
+                        if !self.run(next) {
+                            return false
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_the_solutions_of_a_hand() {
+        let search = solutions(&[3, 8, 1, 1], 24.0, &Rules::default(), MAX_SOLUTIONS);
+        assert!(search.complete);
+        assert_eq!(search.found.len(), 6);
+        for solution in &search.found {
+            assert_eq!(solution.value(), Ok(24.0));
+            let mut numbers = solution.numbers();
+            numbers.sort_unstable();
+            assert_eq!(numbers, vec![1, 1, 3, 8]);
+        }
+    }
+
+    #[test]
+    fn hand_without_solution() {
+        let search = solutions(&[1, 1, 1, 1], 24.0, &Rules::default(), MAX_SOLUTIONS);
+        assert!(search.complete);
+        assert!(search.found.is_empty());
+    }
+
+    #[test]
+    fn stops_at_the_limits() {
+        let search = solutions(&[3, 8, 1, 1], 24.0, &Rules::default(), 1);
+        assert_eq!(search.found.len(), 1);
+        assert!(!search.complete);
+
+        let rules = Rules {
+            operators: vec![
+                Operator::Add,
+                Operator::Sub,
+                Operator::Mul,
+                Operator::Div,
+                Operator::Pow,
+                Operator::Sqrt,
+                Operator::Factorial,
+                Operator::Concat,
+            ],
+            cards: Rules::MAX_CARDS,
+            ..Rules::default()
+        };
+        let search = solutions(&[4, 7, 2, 9, 5, 6], 24.0, &rules, MAX_SOLUTIONS);
+        assert!(!search.complete);
+        assert_eq!(search.count_text(), format!("at least {} distinct solutions", search.found.len()));
+    }
+}
@@ -1,56 +1,82 @@
 use indexmap::{indexmap, IndexMap};
 use super::common::{Card, CardType};
+use super::deck::DeckType;
+use super::art::{custom_back, custom_front};
 use lazy_static::lazy_static;
-use crate::cardascii::game::CardStack;
 
+use std::sync::atomic::{AtomicBool, Ordering};
 
-fn draw_card(card_visual: &Vec<&'static str>) -> String {
+/// The built-in cards are drawn with ASCII characters only
+static ASCII_CARDS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_ascii_cards(ascii: bool) {
+    ASCII_CARDS.store(ascii, Ordering::Relaxed);
+}
+
+
+fn draw_card<S: AsRef<str>>(card_visual: &[S]) -> String {
     let mut res = "".to_string();
     for str in card_visual {
-        res.push_str(str);
+        res.push_str(str.as_ref());
         res.push_str("\n");
     }
     res
 }
 
-fn get_visual_card_from_vec_cards(hand: & Vec<&Card>, index: usize) -> String {
-    draw_card(& CARD_STR_FRONTS.get( *( hand.get(index).unwrap() ) ).unwrap() )
+/// Art of the card: the one of the installed card pack if any,
+/// else the Spanish cards are drawn by hand and the French ones are built from their pips
+fn card_art(card: &Card) -> String {
+    if let Some(art) = custom_front(card) {
+        return art
+    }
+    if ASCII_CARDS.load(Ordering::Relaxed) {
+        return draw_card(&ascii_card(card))
+    }
+    match (CARD_STR_FRONTS.get(card), FRENCH_CARD_FRONTS.get(card)) {
+        (Some(visual), _) => draw_card(visual),
+        (None, Some(visual)) => draw_card(visual),
+        // Not in the built-in decks, i.e. received from a peer with another deck
+        (None, None) => draw_card(&ascii_card(card)),
+    }
 }
 
-fn get_visual_card_from_stack(hand: &CardStack, index: usize) -> String {
-    draw_card(& CARD_STR_FRONTS.get_index( usize::from( * (hand.card_ids.get(index).unwrap() ) ) ).unwrap().1 )
+fn get_visual_card_from_vec_cards(hand: & Vec<&Card>, index: usize) -> String {
+    hand.get(index).map_or_else(draw_card_back, |card| card_art(card))
 }
 
-fn get_visual_card_from_array(hand: & [Card; 4], index: usize) -> String {
-    draw_card(& CARD_STR_FRONTS.get( & hand[index] ).unwrap() )
+
+fn get_visual_card_from_slice(hand: & [Card], index: usize) -> String {
+    hand.get(index).map_or_else(draw_card_back, card_art)
 }
 
 
-fn draw_hand<F>(get_visual_card: F) -> Vec<Vec<String>>
+/// Cards drawn in each row of the hand
+pub const HAND_COLUMNS: usize = 2;
+
+fn draw_hand<F>(count: usize, get_visual_card: F) -> Vec<Vec<String>>
     where
         F: for<'a> Fn(&'a usize) -> String {
-    vec![
-        vec![get_visual_card(& 0usize),
-             get_visual_card(& 1usize)],
-        vec![get_visual_card(& 2usize),
-             get_visual_card(& 3usize)],
-    ]
+    (0..count)
+        .collect::<Vec<_>>()
+        .chunks(HAND_COLUMNS)
+        .map(|row| row.iter().map(&get_visual_card).collect())
+        .collect()
 }
 
 
 pub fn draw_hand_from_vec_cards(hand: & Vec<&Card>) -> Vec<Vec<String>> {
-    draw_hand( |indx: & usize| get_visual_card_from_vec_cards(hand, * indx) )
+    draw_hand( hand.len(), |indx: & usize| get_visual_card_from_vec_cards(hand, * indx) )
 }
 
 
-pub fn draw_hand_from_stack(hand: &CardStack) -> Vec<Vec<String>> {
-    draw_hand( |indx: & usize| get_visual_card_from_stack(hand,* indx) )
-}
+pub fn draw_hand_from_slice(hand: & [Card]) -> Vec<Vec<String>> {
+    draw_hand( hand.len(), |indx: & usize| get_visual_card_from_slice(hand, * indx) )
 
+}
 
-pub fn draw_hand_from_array(hand: & [Card; 4]) -> Vec<Vec<String>> {
-    draw_hand( |indx: & usize| get_visual_card_from_array(hand, * indx) )
 
+pub fn draw_single_card(card: & Card) -> String {
+    card_art(card)
 }
 
 
@@ -69,7 +95,27 @@ macro_rules! make_str_card {
     };
 }
 
-/*fn card_str_back() -> Vec<&'static str>{
+pub fn draw_card_back() -> String {
+    custom_back().unwrap_or_else(|| match ASCII_CARDS.load(Ordering::Relaxed) {
+        true => draw_card(&ascii_card_back()),
+        false => draw_card(&card_str_back()),
+    })
+}
+
+fn ascii_card_back() -> Vec<&'static str> {
+    make_str_card!(
+        r#"+------------+"#,
+        r#"|############|"#,
+        r#"|############|"#,
+        r#"|############|"#,
+        r#"|#CARDASCII!#|"#,
+        r#"|############|"#,
+        r#"|############|"#,
+        r#"|############|"#,
+        r#"+------------+"#)
+}
+
+fn card_str_back() -> Vec<&'static str>{
     make_str_card!(
         r#"┌────────────┐"#,
         r#"│╳╳╳╳╳╳╳╳╳╳╳╳│"#,
@@ -80,13 +126,186 @@ macro_rules! make_str_card {
         r#"│╳╳╳╳╳╳╳╳╳╳╳╳│"#,
         r#"│╳╳╳╳╳╳╳╳╳╳╳╳│"#,
         r#"└────────────┘"#)
-}*/
+}
 
 pub fn make_card( _type: CardType, value: u8) -> Card {
     Card{ _type, value }
 }
 
+pub fn suit_symbol(_type: CardType) -> char {
+    match _type {
+        CardType::Spade => '♠',
+        CardType::Heart => '♥',
+        CardType::Diamond => '♦',
+        CardType::Clover => '♣',
+        CardType::Gold => '⊙',
+        CardType::Club => '♣',
+        CardType::Sword => '⚔',
+        CardType::Cup => '♁',
+        CardType::Joker => '★',
+    }
+}
+
+/// Suit letters of the ASCII cards: the Spanish initials of oros, bastos, espadas and copas,
+/// and the English ones of the French suits
+fn ascii_suit_symbol(_type: CardType) -> char {
+    match _type {
+        CardType::Gold => 'O',
+        CardType::Club => 'B',
+        CardType::Sword => 'E',
+        CardType::Cup => 'C',
+        CardType::Joker => '*',
+        CardType::Spade => 'S',
+        CardType::Heart => 'H',
+        CardType::Diamond => 'D',
+        CardType::Clover => 'C',
+    }
+}
+
+/// Value and suit of the card, i.e. `7♣` or `K♥`
+pub fn card_label(card: &Card) -> String {
+    let rank = match DeckType::FRENCH_SUITS.contains(&card._type) {
+        true => french_rank(card.value),
+        false => card.value.to_string(),
+    };
+    let suit = match ASCII_CARDS.load(Ordering::Relaxed) {
+        true => ascii_suit_symbol(card._type),
+        false => suit_symbol(card._type),
+    };
+    format!("{rank}{suit}")
+}
+
+/// Width of the cards drawn by `draw_mini_card`
+pub const MINI_CARD_WIDTH: usize = 8;
+
+/// Card of 3 lines with its value and suit, the back of the cards if there is no card
+pub fn draw_mini_card(card: Option<&Card>) -> String {
+    const WIDTH: usize = MINI_CARD_WIDTH - 2;
+    let ascii = ASCII_CARDS.load(Ordering::Relaxed);
+    let (horizontal, vertical, corners) = match ascii {
+        true => ('-', '|', ['+', '+', '+', '+']),
+        false => ('─', '│', ['┌', '┐', '└', '┘']),
+    };
+    let content = match card {
+        Some(card) => format!(" {:<width$}", card_label(card), width = WIDTH - 1),
+        None => match ascii {
+            true => "#".repeat(WIDTH),
+            false => "╳".repeat(WIDTH),
+        },
+    };
+    let border = horizontal.to_string().repeat(WIDTH);
+    format!(
+        "{}{border}{}\n{vertical}{content}{vertical}\n{}{border}{}\n",
+        corners[0], corners[1], corners[2], corners[3]
+    )
+}
+
+fn suit_name(_type: CardType) -> &'static str {
+    match _type {
+        CardType::Gold => "COINS",
+        CardType::Club | CardType::Clover => "CLUBS",
+        CardType::Sword => "SWORDS",
+        CardType::Cup => "CUPS",
+        CardType::Joker => "JOKER",
+        CardType::Spade => "SPADES",
+        CardType::Heart => "HEARTS",
+        CardType::Diamond => "DIAMONDS",
+    }
+}
+
+/// Art of a card with ASCII characters only, for the terminals that can't show the others
+fn ascii_card(card: &Card) -> Vec<String> {
+    const WIDTH: usize = 12;
+    let rank = match DeckType::FRENCH_SUITS.contains(&card._type) {
+        true => french_rank(card.value),
+        false => card.value.to_string(),
+    };
+    let border = format!("+{}+", "-".repeat(WIDTH));
+    let empty = format!("|{}|", " ".repeat(WIDTH));
+    vec![
+        border.clone(),
+        format!("|{:<width$}|", rank, width = WIDTH),
+        empty.clone(),
+        format!("|{:^width$}|", suit_name(card._type), width = WIDTH),
+        empty.clone(),
+        format!("|{:^width$}|", rank, width = WIDTH),
+        empty,
+        format!("|{:>width$}|", rank, width = WIDTH),
+        border,
+    ]
+}
+
+/// A, 2...10, J, Q, K
+fn french_rank(value: u8) -> String {
+    match value {
+        1 => String::from("A"),
+        11 => String::from("J"),
+        12 => String::from("Q"),
+        13 => String::from("K"),
+        value => value.to_string(),
+    }
+}
+
+/// Rows (0 to 4) and columns (0 to 2) of the pips of the French cards from A to 10
+fn french_pips(value: u8) -> Vec<(usize, usize)> {
+    let corners = [(0, 0), (0, 2), (4, 0), (4, 2)];
+    let sides = [(0, 0), (0, 2), (2, 0), (2, 2), (4, 0), (4, 2)];
+    let long_sides = [(0, 0), (0, 2), (1, 0), (1, 2), (3, 0), (3, 2), (4, 0), (4, 2)];
+    match value {
+        1 => vec![(2, 1)],
+        2 => vec![(0, 1), (4, 1)],
+        3 => vec![(0, 1), (2, 1), (4, 1)],
+        4 => corners.to_vec(),
+        5 => [&corners[..], &[(2, 1)]].concat(),
+        6 => sides.to_vec(),
+        7 => [&sides[..], &[(1, 1)]].concat(),
+        8 => [&sides[..], &[(1, 1), (3, 1)]].concat(),
+        9 => [&long_sides[..], &[(2, 1)]].concat(),
+        _ => [&long_sides[..], &[(1, 1), (3, 1)]].concat(),
+    }
+}
+
+fn french_face(value: u8, suit: char) -> Vec<String> {
+    let face: [&str; 5] = match value {
+        11 => ["    ___     ", "   (o o)    ", "    \\_/  S  ", "   /| |\\    ", "    |_|     "],
+        12 => ["   .^^^.    ", "   (o o)    ", "    \\~/  S  ", "   /) (\\    ", "   /___\\    "],
+        _ => ["   |^^^|    ", "   (o o)    ", "   {\\_/} S  ", "   /| |\\    ", "    |_|     "],
+    };
+    face.iter().map(|row| row.replace('S', &suit.to_string())).collect()
+}
+
+/// Art of a French card, with the same size as the Spanish ones
+fn french_card(card: &Card) -> Vec<String> {
+    const WIDTH: usize = 12;
+    let suit = suit_symbol(card._type);
+    let rank = french_rank(card.value);
+
+    let body = match card.value {
+        11..=13 => french_face(card.value, suit),
+        value => {
+            let mut rows = vec![vec![' '; WIDTH]; 5];
+            for (row, column) in french_pips(value) {
+                rows[row][3 + column * 3] = suit;
+            }
+            rows.into_iter().map(|row| row.into_iter().collect()).collect()
+        }
+    };
+
+    let mut lines = vec![format!("┌{}┐", "─".repeat(WIDTH))];
+    lines.push(format!("│{:<width$}│", format!("{rank}{suit}"), width = WIDTH));
+    lines.extend(body.iter().map(|row| format!("│{row}│")));
+    lines.push(format!("│{:>width$}│", format!("{suit}{rank}"), width = WIDTH));
+    lines.push(format!("└{}┘", "─".repeat(WIDTH)));
+    lines
+}
+
 lazy_static! {
+    static ref FRENCH_CARD_FRONTS: IndexMap<Card, Vec<String>> = DeckType::French52
+        .cards()
+        .iter()
+        .map(|card| (*card, french_card(card)))
+        .collect();
+
     static ref CARD_STR_FRONTS: IndexMap<Card, Vec<&'static str>> = {
         let map = indexmap! {
             make_card( CardType::Joker, 0 ) => make_str_card!(
@@ -644,3 +863,4 @@ lazy_static! {
 
     };
 }
+
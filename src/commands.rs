@@ -1,6 +1,12 @@
 pub mod send_file;
 pub mod cardascii_answer;
 pub mod cardascii_pass;
+pub mod direct_message;
+pub mod history;
+pub mod export;
+pub mod filter;
+pub mod bot;
+pub mod solo;
 #[cfg(feature = "stream-video")]
 pub mod send_stream;
 
@@ -12,6 +18,18 @@ use std::collections::HashMap;
 pub trait Command {
     fn name(&self) -> &'static str;
     fn parse_params(&self, params: Vec<String>) -> Result<Box<dyn Action>>;
+
+    /// Whether the command invocation is shown as chat to the other users.
+    /// Commands are local by default, so their params (i.e. an answer) are not revealed.
+    fn echo(&self) -> bool {
+        false
+    }
+
+    /// Whether the params are received as the raw text written after the command name
+    /// (a single param) instead of being split as shell words.
+    fn raw_params(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Default)]
@@ -27,12 +45,29 @@ impl CommandManager {
         self
     }
 
+    fn find_command(&self, input: &str) -> Option<&(dyn Command + Send)> {
+        let input = input.strip_prefix(Self::COMMAND_PREFIX)?;
+        let first = input.split(char::is_whitespace).next()?;
+        self.parsers.get(first).map(|parser| parser.as_ref())
+    }
+
+    /// Returns `true` if the input must be sent as chat: plain messages and echoed commands.
+    pub fn is_echoed(&self, input: &str) -> bool {
+        match self.find_command(input) {
+            Some(command) => command.echo(),
+            None => !input.starts_with(Self::COMMAND_PREFIX),
+        }
+    }
+
     pub fn find_command_action(&self, input: &str) -> Option<Result<Box<dyn Action>>> {
         if let Some(input) = input.strip_prefix(Self::COMMAND_PREFIX) {
             let mut input = input.splitn(2, char::is_whitespace);
             if let Some(first) = input.next() {
                 if let Some(parser) = self.parsers.get(first) {
                     let param_str = input.next().unwrap_or("");
+                    if parser.raw_params() {
+                        return Some(parser.parse_params(vec![param_str.to_string()]))
+                    }
                     return match shellwords::split(param_str) {
                         Ok(params) => Some(parser.parse_params(params)),
                         Err(err) => Some(Err(err.into())),
@@ -0,0 +1,103 @@
+use crate::action::{Action, Processing};
+use crate::cardascii::bot::{Bot, Skill};
+use crate::commands::{Command};
+use crate::state::{State};
+use crate::util::{Result, Reportable};
+
+use message_io::network::{NetworkController};
+
+/// Most bots playing at a table
+const MAX_BOTS: usize = 50;
+
+const USAGE: &str = "Use: ?bot add easy|medium|hard [<count>], ?bot remove <name> or ?bot clear";
+
+pub struct BotCommand;
+
+impl Command for BotCommand {
+    fn name(&self) -> &'static str {
+        "bot"
+    }
+
+    fn parse_params(&self, params: Vec<String>) -> Result<Box<dyn Action>> {
+        let mut params = params.into_iter();
+        match params.next().as_deref() {
+            Some("add") => (),
+            Some("remove") => match params.next() {
+                Some(name) => return Ok(Box::new(RemoveBots { name: Some(name) })),
+                None => return Err("The name of the bot to remove is missing".into()),
+            },
+            Some("clear") => return Ok(Box::new(RemoveBots { name: None })),
+            _ => return Err(USAGE.into()),
+        }
+        let skill = match params.next() {
+            Some(skill) => skill.parse::<Skill>()?,
+            None => return Err("The skill of the bot is missing: easy, medium or hard".into()),
+        };
+        let count = match params.next() {
+            Some(count) => match count.parse::<usize>() {
+                Ok(count) if (1..=MAX_BOTS).contains(&count) => count,
+                _ => return Err(format!("The count of bots must be from 1 to {}", MAX_BOTS).into()),
+            },
+            None => 1,
+        };
+        Ok(Box::new(AddBots { skill, count }))
+    }
+}
+
+pub struct AddBots {
+    skill: Skill,
+    count: usize,
+}
+
+impl Action for AddBots {
+    fn process(&mut self, state: &mut State, _network: &NetworkController) -> Processing {
+        let playing = state.bots().len();
+        if playing + self.count > MAX_BOTS {
+            format!("A table can have up to {} bots, it already has {}", MAX_BOTS, playing).report_err(state);
+            return Processing::Completed
+        }
+        let game = match state.game24.as_mut() {
+            Some(game) => game,
+            None => {
+                String::from("Only the table can add bots").report_err(state);
+                return Processing::Completed
+            }
+        };
+        let mut bots = Vec::new();
+        let mut number = 1;
+        while bots.len() < self.count {
+            // The name could be taken by another bot or player
+            let name = format!("{}-bot-{}", self.skill.name(), number);
+            if game.do_user_registration(&name).is_ok() {
+                bots.push(Bot::new(name, self.skill));
+            }
+            number += 1;
+        }
+        // The table announces the bots and plans their moves
+        state.bots_mut().extend(bots);
+        Processing::Completed
+    }
+}
+
+pub struct RemoveBots {
+    // None removes all the bots
+    name: Option<String>,
+}
+
+impl Action for RemoveBots {
+    fn process(&mut self, state: &mut State, _network: &NetworkController) -> Processing {
+        if state.game24.is_none() {
+            String::from("Only the table can remove bots").report_err(state);
+            return Processing::Completed
+        }
+        // The table announces that the bots left and the turn doesn't wait for them
+        let name = self.name.as_deref();
+        let removed = state.remove_bots(|bot| name.is_none_or(|name| bot.name == name));
+        match (name, removed) {
+            (Some(name), 0) => format!("There is no bot called '{}'", name).report_err(state),
+            (None, 0) => String::from("There are no bots at the table").report_err(state),
+            _ => (),
+        }
+        Processing::Completed
+    }
+}
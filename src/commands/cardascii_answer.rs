@@ -3,7 +3,8 @@ use crate::action::{Action, Processing};
 use crate::commands::Command;
 use crate::encoder::Encoder;
 use crate::message::NetMessage;
-use crate::state::State;
+use crate::cardascii::solo::SoloMove;
+use crate::state::{State, ChatMessage, MessageType};
 use crate::util::Result;
 
 pub struct CardasciiAnswerCommand;
@@ -14,20 +15,21 @@ impl Command for CardasciiAnswerCommand {
     }
 
     fn parse_params(&self, params: Vec<String>) -> Result<Box<dyn Action>> {
-        let operation = params
-            .into_iter()
-            .fold(
-                "".to_string(),
-                |mut cur, nxt| {
-                    cur.push_str(format!(" {nxt}").as_str());
-                    cur
-                });
-
-        match CardasciiAnswer::new(operation.clone()) {
+        // The answer is sent as it was written, the table normalizes it
+        let operation = params.into_iter().next().unwrap_or_default();
+        if operation.trim().is_empty() {
+            return Err("No answer specified".into())
+        }
+
+        match CardasciiAnswer::new(operation) {
             Ok(action) => Ok(Box::new(action)),
             Err(e) => Err(e),
         }
     }
+
+    fn raw_params(&self) -> bool {
+        true
+    }
 }
 
 pub struct CardasciiAnswer {
@@ -45,14 +47,27 @@ impl CardasciiAnswer {
 impl Action for CardasciiAnswer {
     fn process(&mut self, state: &mut State, network: &NetworkController) -> Processing {
 
-        let net_message = NetMessage::CardasciiAnswer( self.operation.clone() );
+        let turn = state.current_turn();
+        match state.solo_mut() {
+            // In solo mode the local game checks the answer
+            Some(solo) => solo.push_move(SoloMove::Answer(turn, self.operation.clone())),
+            None => {
+                let net_message = NetMessage::CardasciiAnswer(turn, self.operation.clone());
 
-        let message = self.encoder.encode(net_message);
+                let message = self.encoder.encode(net_message);
 
-        for endpoint in state.all_user_endpoints() {
-            network.send(*endpoint, message);
+                for endpoint in state.all_user_endpoints() {
+                    network.send(*endpoint, message);
+                }
+            }
         }
 
+        // The answer is only shown to us, the table will notify the result to everyone.
+        let message = ChatMessage::new(
+            format!("{} (me)", state.local_user),
+            MessageType::Game(format!("24Game_answer! > {}", self.operation)),
+        );
+        state.add_message(message);
 
         Processing::Completed
     }
@@ -3,7 +3,8 @@ use crate::action::{Action, Processing};
 use crate::commands::Command;
 use crate::encoder::Encoder;
 use crate::message::NetMessage;
-use crate::state::State;
+use crate::cardascii::solo::SoloMove;
+use crate::state::{State, ChatMessage, MessageType};
 use crate::util::Result;
 
 pub struct CardasciiPassCommand;
@@ -35,15 +36,26 @@ impl CardasciiPass {
 
 impl Action for CardasciiPass {
     fn process(&mut self, state: &mut State, network: &NetworkController) -> Processing {
+        // Alone, passing is skipping the hand
+        if let Some(solo) = state.solo_mut() {
+            solo.push_move(SoloMove::Skip);
+            return Processing::Completed
+        }
 
-        let net_message = NetMessage::CardasciiPass();
+        let net_message = NetMessage::CardasciiPass(state.current_turn());
 
         let message = self.encoder.encode(net_message);
 
         for endpoint in state.all_user_endpoints() {
             network.send(*endpoint, message);
         }
-        
+
+        let message = ChatMessage::new(
+            format!("{} (me)", state.local_user),
+            MessageType::Game(String::from("passed this turn")),
+        );
+        state.add_message(message);
+
         Processing::Completed
     }
 }
@@ -0,0 +1,101 @@
+use crate::action::{Action, Processing};
+use crate::commands::{Command};
+use crate::state::{State, ChatMessage, MessageType};
+use crate::message::{NetMessage};
+use crate::util::{Result, Reportable};
+use crate::encoder::{Encoder};
+
+use message_io::network::{NetworkController, SendStatus};
+
+pub struct DirectMessageCommand;
+
+impl Command for DirectMessageCommand {
+    fn name(&self) -> &'static str {
+        "msg"
+    }
+
+    fn parse_params(&self, params: Vec<String>) -> Result<Box<dyn Action>> {
+        // Only the user is split off, the message is sent as it was written
+        let text = params.into_iter().next().unwrap_or_default();
+        let (user, content) = text.trim_start().split_once(char::is_whitespace).unwrap_or((text.trim(), ""));
+        if user.is_empty() {
+            return Err("No user specified".into())
+        }
+        Ok(Box::new(DirectMessage::new(Some(user.to_string()), content.trim_start().to_string())?))
+    }
+
+    fn raw_params(&self) -> bool {
+        true
+    }
+}
+
+pub struct ReplyCommand;
+
+impl Command for ReplyCommand {
+    fn name(&self) -> &'static str {
+        "r"
+    }
+
+    fn parse_params(&self, params: Vec<String>) -> Result<Box<dyn Action>> {
+        let content = params.into_iter().next().unwrap_or_default();
+        Ok(Box::new(DirectMessage::new(None, content.trim_start().to_string())?))
+    }
+
+    fn raw_params(&self) -> bool {
+        true
+    }
+}
+
+pub struct DirectMessage {
+    // None means: reply to the last user that sent us a private message
+    user: Option<String>,
+    content: String,
+    encoder: Encoder,
+}
+
+impl DirectMessage {
+    pub fn new(user: Option<String>, content: String) -> Result<DirectMessage> {
+        if content.trim().is_empty() {
+            return Err("No message specified".into())
+        }
+        Ok(DirectMessage { user, content, encoder: Encoder::new() })
+    }
+}
+
+impl Action for DirectMessage {
+    fn process(&mut self, state: &mut State, network: &NetworkController) -> Processing {
+        let user = match self.user.clone().or_else(|| state.last_private_user().cloned()) {
+            Some(user) => user,
+            None => {
+                String::from("Nobody sent you a private message yet").report_err(state);
+                return Processing::Completed
+            }
+        };
+
+        let net_message =
+            NetMessage::DirectMessage(state.local_user.clone(), user.clone(), self.content.clone());
+        let message = self.encoder.encode(net_message);
+
+        // If the user is not directly connected, the table will relay the message
+        // or tell us that the user is not connected.
+        let endpoint = match state.user_endpoint(&user).or_else(|| state.table_endpoint()) {
+            Some(endpoint) => *endpoint,
+            None => {
+                format!("User '{}' is not connected", user).report_err(state);
+                return Processing::Completed
+            }
+        };
+        if network.send(endpoint, message) != SendStatus::Sent {
+            format!("Could not send the private message to '{}'", user).report_err(state);
+            return Processing::Completed
+        }
+
+        let message = ChatMessage::new(
+            format!("{} (me) to {}", state.local_user, user),
+            MessageType::Private(self.content.clone()),
+        );
+        state.add_message(message);
+
+        Processing::Completed
+    }
+}
@@ -0,0 +1,283 @@
+use crate::action::{Action, Processing};
+use crate::cardascii::common::{Card, TurnOutcome, TurnRecord};
+use crate::cardascii::solo::Solo;
+use crate::cardascii::solver::count_text;
+use crate::commands::{Command};
+use crate::state::{State, ChatMessage, MessageType, SystemMessageType};
+use crate::util::{Result, Reportable};
+
+use chrono::{Local, TimeZone};
+use message_io::network::{NetworkController};
+use serde::Serialize;
+
+use std::path::{Path, PathBuf};
+
+pub struct ExportCommand;
+
+impl Command for ExportCommand {
+    fn name(&self) -> &'static str {
+        "export"
+    }
+
+    fn parse_params(&self, params: Vec<String>) -> Result<Box<dyn Action>> {
+        let mut path = None;
+        let mut format = None;
+        let mut params = params.into_iter();
+        while let Some(param) = params.next() {
+            match param.as_str() {
+                "--format" | "-f" => {
+                    let value = params.next().ok_or("No format specified")?;
+                    format = Some(ExportFormat::parse(&value)?);
+                }
+                _ => match param.strip_prefix("--format=") {
+                    Some(value) => format = Some(ExportFormat::parse(value)?),
+                    None => path = Some(shellexpand::full(&param)?.to_string()),
+                },
+            }
+        }
+
+        let path = PathBuf::from(path.ok_or("No file specified")?);
+        let format = format.unwrap_or_else(|| ExportFormat::from_extension(&path));
+        Ok(Box::new(Export { path, format }))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ExportFormat {
+    Txt,
+    Json,
+    Md,
+}
+
+impl ExportFormat {
+    fn parse(value: &str) -> Result<ExportFormat> {
+        match value {
+            "txt" => Ok(ExportFormat::Txt),
+            "json" => Ok(ExportFormat::Json),
+            "md" => Ok(ExportFormat::Md),
+            _ => Err(format!("Unknown format '{}', use txt, json or md", value).into()),
+        }
+    }
+
+    fn from_extension(path: &Path) -> ExportFormat {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => ExportFormat::Json,
+            Some("md") => ExportFormat::Md,
+            _ => ExportFormat::Txt,
+        }
+    }
+}
+
+pub struct Export {
+    path: PathBuf,
+    format: ExportFormat,
+}
+
+impl Action for Export {
+    fn process(&mut self, state: &mut State, _network: &NetworkController) -> Processing {
+        let log = ExportLog::new(state);
+        let content = match self.format {
+            ExportFormat::Txt => Ok(log.to_txt()),
+            ExportFormat::Json => log.to_json(),
+            ExportFormat::Md => Ok(log.to_md()),
+        };
+
+        match content.and_then(|content| Ok(std::fs::write(&self.path, content)?)) {
+            Ok(()) => format!("Log exported to '{}'", self.path.display()).report_info(state),
+            Err(e) => format!("Unable to export the log: {}", e).report_err(state),
+        }
+        Processing::Completed
+    }
+}
+
+// The json output is consumed by external scripts:
+// new fields can be added, but existing ones must not be renamed or removed.
+#[derive(Serialize)]
+struct ExportLog {
+    version: u32,
+    exported_at: String,
+    messages: Vec<ExportMessage>,
+    turns: Vec<ExportTurn>,
+    /// Seed of the deck of the game, to replay it with `--seed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    /// Result line of the solo session, to compare it with other players
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ExportMessage {
+    date: String,
+    user: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    content: String,
+}
+
+#[derive(Serialize)]
+struct ExportTurn {
+    date: String,
+    turn: u32,
+    cards: Vec<ExportCard>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_card: Option<ExportCard>,
+    result: &'static str,
+    winner: Option<String>,
+    answer: Option<String>,
+    solutions: usize,
+    /// False when the hand had at least `solutions`
+    solutions_complete: bool,
+}
+
+#[derive(Serialize)]
+struct ExportCard {
+    suit: String,
+    value: u8,
+}
+
+impl ExportLog {
+    const VERSION: u32 = 1;
+
+    fn new(state: &State) -> ExportLog {
+        let messages = state
+            .history_messages()
+            .iter()
+            .chain(state.messages().iter())
+            .filter_map(ExportMessage::new)
+            .collect();
+        let turns = state.turn_results().iter().map(ExportTurn::new).collect();
+
+        ExportLog {
+            version: Self::VERSION,
+            exported_at: Local::now().to_rfc3339(),
+            messages,
+            turns,
+            seed: state.seed(),
+            result: state.solo().map(Solo::result_line),
+        }
+    }
+
+    fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    fn to_txt(&self) -> String {
+        let mut out = String::new();
+        for message in &self.messages {
+            out.push_str(&format!("[{}] {}\n", message.date, message.line()));
+        }
+        if !self.turns.is_empty() {
+            out.push('\n');
+        }
+        if let Some(seed) = self.seed {
+            out.push_str(&format!("deck seed {}\n", seed));
+        }
+        for turn in &self.turns {
+            out.push_str(&format!("[{}] {}\n", turn.date, turn.line()));
+        }
+        if let Some(result) = &self.result {
+            out.push_str(&format!("\n{}\n", result));
+        }
+        out
+    }
+
+    fn to_md(&self) -> String {
+        let mut out = format!("# termchat log ({})\n\n## Messages\n\n", self.exported_at);
+        for message in &self.messages {
+            out.push_str(&format!("- `{}` {}\n", message.date, message.line()));
+        }
+        out.push_str("\n## Turns\n\n");
+        if let Some(seed) = self.seed {
+            out.push_str(&format!("Deck seed: {}\n\n", seed));
+        }
+        out.push_str("| Turn | Date | Cards | Result |\n|---|---|---|---|\n");
+        for turn in &self.turns {
+            let cards = turn.cards.iter().map(ExportCard::text).collect::<Vec<_>>().join(", ");
+            let result = turn.line().replace('|', "\\|");
+            out.push_str(&format!("| {} | {} | {} | {} |\n", turn.turn, turn.date, cards, result));
+        }
+        if let Some(result) = &self.result {
+            out.push_str(&format!("\n## Result\n\n{}\n", result));
+        }
+        out
+    }
+}
+
+impl ExportMessage {
+    fn new(message: &ChatMessage) -> Option<ExportMessage> {
+        let (kind, content) = match &message.message_type {
+            MessageType::Connection => ("connection", String::new()),
+            MessageType::Disconnection => ("disconnection", String::new()),
+            MessageType::Text(content) => ("text", content.clone()),
+            MessageType::Private(content) => ("private", content.clone()),
+            MessageType::Game(content) => ("game", content.clone()),
+            MessageType::System(content, SystemMessageType::Info) => ("info", content.clone()),
+            MessageType::System(content, SystemMessageType::Warning) => ("warning", content.clone()),
+            MessageType::System(content, SystemMessageType::Error) => ("error", content.clone()),
+            // Progress bars are not part of the conversation
+            MessageType::Progress(_) => return None,
+        };
+        Some(ExportMessage {
+            date: message.date.to_rfc3339(),
+            user: message.user.trim_end_matches(": ").into(),
+            kind,
+            content,
+        })
+    }
+
+    fn line(&self) -> String {
+        match self.kind {
+            "connection" => format!("{} is online", self.user),
+            "disconnection" => format!("{} is offline", self.user),
+            "private" => format!("{} (private): {}", self.user, self.content),
+            "text" => format!("{}: {}", self.user, self.content),
+            level => format!("{} ({}): {}", self.user, level, self.content),
+        }
+    }
+}
+
+impl ExportTurn {
+    fn new(record: &TurnRecord) -> ExportTurn {
+        let date = Local.timestamp_opt(record.timestamp, 0).single().unwrap_or_else(Local::now);
+        let (result, winner, answer) = match &record.outcome {
+            TurnOutcome::Winner(user, answer) => ("winner", Some(user.clone()), Some(answer.clone())),
+            TurnOutcome::Tie => ("tie", None, None),
+        };
+        ExportTurn {
+            date: date.to_rfc3339(),
+            turn: record.turn,
+            cards: record.cards.iter().map(ExportCard::new).collect(),
+            target_card: record.target_card.as_ref().map(ExportCard::new),
+            result,
+            winner,
+            answer,
+            solutions: record.solutions,
+            solutions_complete: record.solutions_complete,
+        }
+    }
+
+    fn line(&self) -> String {
+        let result = match (&self.winner, &self.answer) {
+            (Some(winner), Some(answer)) => {
+                format!("turn {}: {} won with {}", self.turn, winner, answer.trim())
+            }
+            _ => format!("turn {}: tie, all players passed", self.turn),
+        };
+        let target = match &self.target_card {
+            Some(card) => format!(", target {}", card.text()),
+            None => String::new(),
+        };
+        format!("{} ({}{})", result, count_text(self.solutions, self.solutions_complete), target)
+    }
+}
+
+impl ExportCard {
+    fn new(card: &Card) -> ExportCard {
+        ExportCard { suit: format!("{:?}", card._type), value: card.value }
+    }
+
+    fn text(&self) -> String {
+        format!("{} {}", self.value, self.suit)
+    }
+}
@@ -0,0 +1,38 @@
+use crate::action::{Action, Processing};
+use crate::commands::{Command};
+use crate::state::{State, MessageFilter};
+use crate::util::{Result};
+
+use message_io::network::{NetworkController};
+
+pub struct FilterCommand;
+
+impl Command for FilterCommand {
+    fn name(&self) -> &'static str {
+        "filter"
+    }
+
+    fn parse_params(&self, params: Vec<String>) -> Result<Box<dyn Action>> {
+        let filter = match params.first().map(|param| param.as_str()) {
+            None | Some("all") => MessageFilter::All,
+            Some("chat") => MessageFilter::Chat,
+            Some("game") => MessageFilter::Game,
+            Some("system") => MessageFilter::System,
+            Some(other) => {
+                return Err(format!("Unknown filter '{}', use all, chat, game or system", other).into())
+            }
+        };
+        Ok(Box::new(Filter { filter }))
+    }
+}
+
+pub struct Filter {
+    filter: MessageFilter,
+}
+
+impl Action for Filter {
+    fn process(&mut self, state: &mut State, _network: &NetworkController) -> Processing {
+        state.set_message_filter(self.filter);
+        Processing::Completed
+    }
+}
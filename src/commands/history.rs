@@ -0,0 +1,57 @@
+use crate::action::{Action, Processing};
+use crate::commands::{Command};
+use crate::state::{State};
+use crate::message::{NetMessage};
+use crate::util::{Result, Reportable};
+use crate::encoder::{Encoder};
+
+use message_io::network::{NetworkController};
+
+pub struct HistoryCommand;
+
+impl Command for HistoryCommand {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn parse_params(&self, params: Vec<String>) -> Result<Box<dyn Action>> {
+        let count = match params.first() {
+            Some(count) => count.parse().map_err(|_| "The number of messages must be a number")?,
+            None => LoadHistory::DEFAULT_COUNT,
+        };
+        Ok(Box::new(LoadHistory { count, encoder: Encoder::new() }))
+    }
+}
+
+pub struct LoadHistory {
+    count: usize,
+    encoder: Encoder,
+}
+
+impl LoadHistory {
+    const DEFAULT_COUNT: usize = 20;
+}
+
+impl Action for LoadHistory {
+    fn process(&mut self, state: &mut State, network: &NetworkController) -> Processing {
+        let before = state.oldest_history_id();
+        match &state.history {
+            // We are the table, the history is ours.
+            Some(history) => {
+                let (entries, oldest_id) =
+                    history.page(before.unwrap_or_else(|| history.next_id()), self.count);
+                if entries.is_empty() {
+                    String::from("There are no older messages").report_info(state);
+                }
+                state.add_history(entries, oldest_id);
+            }
+            None => {
+                let message = self.encoder.encode(NetMessage::HistoryRequest(before, self.count));
+                for endpoint in state.all_user_endpoints() {
+                    network.send(*endpoint, message);
+                }
+            }
+        }
+        Processing::Completed
+    }
+}
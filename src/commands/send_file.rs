@@ -4,8 +4,9 @@ use crate::state::{State};
 use crate::message::{NetMessage, Chunk};
 use crate::util::{Result, Reportable};
 use crate::encoder::{Encoder};
+use crate::flow_control::{self, FlowControl};
 
-use message_io::network::{NetworkController};
+use message_io::network::{NetworkController, Endpoint};
 
 use std::path::{Path};
 use std::io::{Read};
@@ -34,10 +35,18 @@ pub struct SendFile {
     file_size: u64,
     progress_id: Option<usize>,
     encoder: Encoder,
+    flow: FlowControl,
+    // Endpoints that receive the file, fixed when the sending starts.
+    receivers: Option<Vec<Endpoint>>,
+    // Last encoded chunk and the receivers that have not accepted it yet.
+    pending: Option<(Vec<u8>, Vec<Endpoint>)>,
+    finished: bool,
 }
 
 impl SendFile {
     const CHUNK_SIZE: usize = 32768;
+    const MIN_DELAY: Duration = Duration::from_micros(100);
+    const MAX_DELAY: Duration = Duration::from_millis(500);
 
     pub fn new(file_path: &str) -> Result<SendFile> {
         const READ_FILENAME_ERROR: &str = "Unable to read file name";
@@ -52,7 +61,35 @@ impl SendFile {
         let file_size = std::fs::metadata(file_path)?.len();
         let file = std::fs::File::open(file_path)?;
 
-        Ok(SendFile { file, file_name, file_size, progress_id: None, encoder: Encoder::new() })
+        Ok(SendFile {
+            file,
+            file_name,
+            file_size,
+            progress_id: None,
+            encoder: Encoder::new(),
+            flow: FlowControl::new(Self::MIN_DELAY, Self::MAX_DELAY),
+            receivers: None,
+            pending: None,
+            finished: false,
+        })
+    }
+
+    /// Sends the pending chunk to the receivers that have not accepted it yet.
+    /// Returns `true` if there is no more data pending to send.
+    fn flush_pending(&mut self, state: &mut State, network: &NetworkController) -> bool {
+        if let Some((message, endpoints)) = self.pending.take() {
+            let report = flow_control::send_all(network, endpoints.iter(), &message);
+            if let Some(receivers) = &mut self.receivers {
+                receivers.retain(|endpoint| !report.failed.iter().any(|(e, _)| e == endpoint));
+            }
+            self.flow.adapt(&report);
+            report.failed.report_if_err(state);
+            if !report.busy.is_empty() {
+                self.pending = Some((message, report.busy));
+                return false
+            }
+        }
+        true
     }
 }
 
@@ -62,29 +99,39 @@ impl Action for SendFile {
             let id = state.add_progress_message(&self.file_name, self.file_size);
             self.progress_id = Some(id);
         }
+        if self.receivers.is_none() {
+            self.receivers = Some(state.all_user_endpoints().cloned().collect());
+        }
+
+        // Do not read more data until every receiver has accepted the previous chunk.
+        if !self.flush_pending(state, network) {
+            return Processing::Partial(self.flow.delay())
+        }
+        if self.finished {
+            return Processing::Completed
+        }
 
         let mut data = [0; Self::CHUNK_SIZE];
-        let (bytes_read, chunk, processing) = match self.file.read(&mut data) {
-            Ok(0) => (0, Chunk::End, Processing::Completed),
-            Ok(bytes_read) => {
-                // We add a minor delay to introduce a rate in the sending.
-                let processing = Processing::Partial(Duration::from_micros(100));
-                (bytes_read, Chunk::Data(data[..bytes_read].to_vec()), processing)
-            }
+        let (bytes_read, chunk) = match self.file.read(&mut data) {
+            Ok(0) => (0, Chunk::End),
+            Ok(bytes_read) => (bytes_read, Chunk::Data(data[..bytes_read].to_vec())),
             Err(error) => {
                 format!("Error sending file. error: {}", error).report_err(state);
-                (0, Chunk::Error, Processing::Completed)
+                (0, Chunk::Error)
             }
         };
+        self.finished = !matches!(chunk, Chunk::Data(_));
 
         state.progress_message_update(self.progress_id.unwrap(), bytes_read as u64);
 
         let net_message = NetMessage::UserData(self.file_name.clone(), chunk);
-        let message = self.encoder.encode(net_message);
-        for endpoint in state.all_user_endpoints() {
-            network.send(*endpoint, message);
-        }
+        let message = self.encoder.encode(net_message).to_vec();
+        let receivers = self.receivers.clone().unwrap_or_default();
+        self.pending = Some((message, receivers));
 
-        processing
+        if self.flush_pending(state, network) && self.finished {
+            return Processing::Completed
+        }
+        Processing::Partial(self.flow.delay())
     }
 }
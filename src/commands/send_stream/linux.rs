@@ -4,8 +4,9 @@ use crate::state::{State};
 use crate::message::{NetMessage};
 use crate::util::{Result, Reportable};
 use crate::encoder::{Encoder};
+use crate::flow_control::{self, FlowControl};
 
-use message_io::network::{NetworkController, Endpoint};
+use message_io::network::{NetworkController};
 use resize::px::RGB;
 use rgb::RGB8;
 use v4l::prelude::*;
@@ -36,9 +37,13 @@ pub struct SendStream {
     width: usize,
     height: usize,
     encoder: Encoder,
+    flow: FlowControl,
 }
 
 impl SendStream {
+    const FRAME_DELAY: Duration = Duration::from_millis(16); //~60fps - delay of computation
+    const MAX_FRAME_DELAY: Duration = Duration::from_millis(500);
+
     pub fn new() -> Result<SendStream> {
         let dev = Device::new(0).expect("Failed to open device");
         let mut fmt = dev.format()?;
@@ -49,7 +54,13 @@ impl SendStream {
 
         let stream = MmapStream::with_buffers(&dev, Type::VideoCapture, 4)?;
 
-        Ok(SendStream { stream, width, height, encoder: Encoder::new() })
+        Ok(SendStream {
+            stream,
+            width,
+            height,
+            encoder: Encoder::new(),
+            flow: FlowControl::new(Self::FRAME_DELAY, Self::MAX_FRAME_DELAY),
+        })
     }
 }
 
@@ -58,14 +69,14 @@ impl Action for SendStream {
         if state.stop_stream {
             // stop stream and restore stop_stream to false for the next stream usage
             state.stop_stream = false;
-            self.send_all(network, state.all_user_endpoints(), NetMessage::Stream(None));
+            self.send_all(network, state, NetMessage::Stream(None));
             return Processing::Completed
         }
         let (data, _metadata) = match self.stream.next() {
             Ok(d) => d,
             Err(e) => {
                 e.to_string().report_err(state);
-                self.send_all(network, state.all_user_endpoints(), NetMessage::Stream(None));
+                self.send_all(network, state, NetMessage::Stream(None));
                 return Processing::Completed
             }
         };
@@ -91,23 +102,20 @@ impl Action for SendStream {
         });
 
         let message = NetMessage::Stream(Some((data, self.width, self.height)));
-        self.send_all(network, state.all_user_endpoints(), message);
+        self.send_all(network, state, message);
 
-        Processing::Partial(Duration::from_millis(16)) //~60fps - delay of computation
+        Processing::Partial(self.flow.delay())
     }
 }
 
 impl SendStream {
-    fn send_all<'a>(
-        &mut self,
-        network: &NetworkController,
-        endpoints: impl Iterator<Item = &'a Endpoint>,
-        net_message: NetMessage,
-    ) {
+    /// Frames not accepted by an endpoint are dropped (not resent),
+    /// but the frame rate is reduced until the endpoints accept them again.
+    fn send_all(&mut self, network: &NetworkController, state: &mut State, net_message: NetMessage) {
         let message = self.encoder.encode(net_message);
-        for endpoint in endpoints {
-            network.send(*endpoint, message);
-        }
+        let report = flow_control::send_all(network, state.all_user_endpoints(), message);
+        self.flow.adapt(&report);
+        report.failed.report_if_err(state);
     }
 }
 
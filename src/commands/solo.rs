@@ -0,0 +1,73 @@
+use crate::action::{Action, Processing};
+use crate::cardascii::solo::SoloMove;
+use crate::commands::{Command};
+use crate::state::{State};
+use crate::util::{Result, Reportable};
+
+use message_io::network::{NetworkController};
+
+pub struct SkipCommand;
+
+impl Command for SkipCommand {
+    fn name(&self) -> &'static str {
+        "skip"
+    }
+
+    fn parse_params(&self, _params: Vec<String>) -> Result<Box<dyn Action>> {
+        Ok(Box::new(SoloPlay { solo_move: SoloMove::Skip }))
+    }
+}
+
+pub struct RevealCommand;
+
+impl Command for RevealCommand {
+    fn name(&self) -> &'static str {
+        "reveal"
+    }
+
+    fn parse_params(&self, _params: Vec<String>) -> Result<Box<dyn Action>> {
+        Ok(Box::new(SoloPlay { solo_move: SoloMove::Reveal }))
+    }
+}
+
+pub struct StatsCommand;
+
+impl Command for StatsCommand {
+    fn name(&self) -> &'static str {
+        "stats"
+    }
+
+    fn parse_params(&self, _params: Vec<String>) -> Result<Box<dyn Action>> {
+        Ok(Box::new(Stats))
+    }
+}
+
+pub struct SoloPlay {
+    solo_move: SoloMove,
+}
+
+impl Action for SoloPlay {
+    fn process(&mut self, state: &mut State, _network: &NetworkController) -> Processing {
+        match state.solo_mut() {
+            Some(solo) => solo.push_move(self.solo_move.clone()),
+            None => String::from("Only available in solo mode (--solo)").report_err(state),
+        }
+        Processing::Completed
+    }
+}
+
+pub struct Stats;
+
+impl Action for Stats {
+    fn process(&mut self, state: &mut State, _network: &NetworkController) -> Processing {
+        match state.solo() {
+            Some(solo) => {
+                let result_line = solo.result_line();
+                solo.summary().report_info(state);
+                result_line.report_info(state);
+            }
+            None => String::from("Only available in solo mode (--solo)").report_err(state),
+        }
+        Processing::Completed
+    }
+}
@@ -1,7 +1,11 @@
 use std::net::{SocketAddrV4};
+use std::path::PathBuf;
 use clap::ArgMatches;
 use serde::{Serialize, Deserialize};
 use crate::util::Result;
+use crate::cardascii::rules::Rules;
+use crate::cardascii::art::CardCharset;
+use crate::cardascii::common::CardType;
 use tui::style::Color;
 #[derive(Serialize, Deserialize, Debug)]
 pub enum NodeType {
@@ -10,7 +14,9 @@ pub enum NodeType {
     },
     Server{
         port: u16,
-    }
+    },
+    /// Offline practice, the game runs locally without connections
+    Solo,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -21,6 +27,32 @@ pub struct Config {
     pub terminal_bell: bool,
     pub theme: Theme,
     pub boot: bool,
+    /// Max number of chat and game entries kept on disk by the table
+    #[serde(default = "Config::default_history_size")]
+    pub history_size: usize,
+    /// Number of history entries sent to a player when joining the table
+    #[serde(default = "Config::default_history_replay")]
+    pub history_replay: usize,
+    /// Rules of the game, used when the instance is the table
+    #[serde(default)]
+    pub rules: Rules,
+    /// Card art pack used to draw the cards, a directory at `$ConfigDir/termchat/cards`
+    #[serde(default)]
+    pub card_pack: Option<String>,
+    /// Characters of the built-in cards: `auto`, `unicode` or `ascii`
+    #[serde(default)]
+    pub card_charset: CardCharset,
+    /// Seed of the order of the cards of the table or solo mode, chosen with `--seed`
+    #[serde(skip)]
+    pub seed: Option<u64>,
+    /// Solo mode playing the daily puzzle, chosen with `--daily`
+    #[serde(skip)]
+    pub daily: bool,
+}
+
+/// Directory where termchat stores its configuration and data files
+pub fn config_dir() -> Option<PathBuf> {
+    Some(dirs_next::config_dir()?.join("termchat"))
 }
 
 impl Default for Config {
@@ -33,16 +65,31 @@ impl Default for Config {
             terminal_bell: true,
             theme: Theme::default(),
             boot: false,
+            history_size: Config::default_history_size(),
+            history_replay: Config::default_history_replay(),
+            rules: Rules::default(),
+            card_pack: None,
+            card_charset: CardCharset::default(),
+            seed: None,
+            daily: false,
         }
     }
 }
 
 impl Config {
+    fn default_history_size() -> usize {
+        500
+    }
+
+    fn default_history_replay() -> usize {
+        20
+    }
+
     /// Try to read config file from disk
     /// If it does not exist, create it with default config values, and return that
     /// If it fails for any other reason return None
     fn from_config_file() -> Option<Self> {
-        let config_dir_path = dirs_next::config_dir()?.join("termchat");
+        let config_dir_path = config_dir()?;
         if let Err(e) = std::fs::create_dir_all(&config_dir_path) {
             if e.kind() != std::io::ErrorKind::AlreadyExists {
                 return None
@@ -69,6 +116,10 @@ impl Config {
         }
     }
 
+    pub fn is_solo(&self) -> bool {
+        matches!(self.node_type, NodeType::Solo)
+    }
+
     /// Read configuration file from disk
     /// If it fails for any reason use default Config value
     /// If the user uses the cli arguments they will override the default values
@@ -86,9 +137,21 @@ impl Config {
         if let Some(server_addr) = matches.value_of("player") {
             config.node_type = NodeType::Client{server_addr: server_addr.parse::<SocketAddrV4>().unwrap()};
         }
+        if let Some(seed) = matches.value_of("seed") {
+            config.seed = Some(seed.parse().unwrap());
+        }
+        config.daily = matches.is_present("daily");
+        // Without `--table`, the seed is played alone
+        if matches.is_present("solo") || config.daily || (config.seed.is_some() && !config.boot) {
+            config.node_type = NodeType::Solo;
+            config.boot = false;
+        }
         if let Some(user_name) = matches.value_of("username") {
             config.user_name = user_name.parse().unwrap();
         }
+        if matches.is_present("sealed") {
+            config.rules.sealed_answers = true;
+        }
         if matches.is_present("quiet-mode") {
             config.terminal_bell = false;
         }
@@ -117,6 +180,55 @@ pub struct Theme {
     pub progress_bar_color: Color,
     pub command_color: Color,
     pub input_panel_color: Color,
+    #[serde(default = "Theme::default_game_event_color")]
+    pub game_event_color: Color,
+    #[serde(default = "Theme::default_search_color")]
+    pub search_color: Color,
+    #[serde(default = "Theme::default_card_selection_color")]
+    pub card_selection_color: Color,
+    #[serde(default)]
+    pub suit_colors: SuitColors,
+}
+
+/// Color of the cards of each suit
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct SuitColors {
+    pub gold: Color,
+    pub club: Color,
+    pub sword: Color,
+    pub cup: Color,
+    pub joker: Color,
+    pub spade: Color,
+    pub heart: Color,
+    pub diamond: Color,
+    pub clover: Color,
+}
+
+impl Default for SuitColors {
+    fn default() -> Self {
+        Self::dark_theme()
+    }
+}
+
+impl SuitColors {
+    fn dark_theme() -> Self {
+        Self {
+            gold: Color::Yellow,
+            club: Color::Green,
+            sword: Color::LightBlue,
+            cup: Color::LightRed,
+            joker: Color::Magenta,
+            spade: Color::White,
+            heart: Color::LightRed,
+            diamond: Color::LightRed,
+            clover: Color::White,
+        }
+    }
+
+    fn light_theme() -> Self {
+        Self { spade: Color::Black, clover: Color::Black, ..Self::dark_theme() }
+    }
 }
 
 impl Default for Theme {
@@ -126,6 +238,33 @@ impl Default for Theme {
 }
 
 impl Theme {
+    fn default_game_event_color() -> Color {
+        Color::LightMagenta
+    }
+
+    fn default_search_color() -> Color {
+        Color::Yellow
+    }
+
+    fn default_card_selection_color() -> Color {
+        Color::LightGreen
+    }
+
+    pub fn suit_color(&self, suit: CardType) -> Color {
+        let colors = &self.suit_colors;
+        match suit {
+            CardType::Gold => colors.gold,
+            CardType::Club => colors.club,
+            CardType::Sword => colors.sword,
+            CardType::Cup => colors.cup,
+            CardType::Joker => colors.joker,
+            CardType::Spade => colors.spade,
+            CardType::Heart => colors.heart,
+            CardType::Diamond => colors.diamond,
+            CardType::Clover => colors.clover,
+        }
+    }
+
     fn dark_theme() -> Self {
         Self {
             message_colors: vec![Color::Blue, Color::Yellow, Color::Cyan, Color::Magenta],
@@ -138,6 +277,10 @@ impl Theme {
             progress_bar_color: Color::LightGreen,
             command_color: Color::LightYellow,
             input_panel_color: Color::White,
+            game_event_color: Theme::default_game_event_color(),
+            search_color: Theme::default_search_color(),
+            card_selection_color: Theme::default_card_selection_color(),
+            suit_colors: SuitColors::dark_theme(),
         }
     }
 
@@ -153,6 +296,10 @@ impl Theme {
             progress_bar_color: Color::LightGreen,
             command_color: Color::LightYellow,
             input_panel_color: Color::Black,
+            game_event_color: Color::Magenta,
+            search_color: Theme::default_search_color(),
+            card_selection_color: Theme::default_card_selection_color(),
+            suit_colors: SuitColors::light_theme(),
         }
     }
 }
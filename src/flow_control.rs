@@ -0,0 +1,73 @@
+use message_io::network::{Endpoint, NetworkController, SendStatus};
+
+use std::time::{Duration, Instant};
+
+/// Adapts the delay between two sending steps of an action depending on how the
+/// endpoints are accepting the data.
+/// Some transports (i.e. WebSocket) block in `send` until the data is written instead of
+/// reporting that the endpoint is busy, so the time spent sending is also measured:
+/// a step that takes longer than the delay means the link can't keep up.
+/// Each time an endpoint is not able to receive data or the link is slower than the pace,
+/// the delay is doubled (up to `max`), otherwise it is halved (down to `min`).
+pub struct FlowControl {
+    delay: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+impl FlowControl {
+    pub fn new(min: Duration, max: Duration) -> FlowControl {
+        FlowControl { delay: min, min, max }
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Adapts the delay to the result of the last sending step
+    pub fn adapt(&mut self, report: &SendReport) {
+        match report.busy.is_empty() && report.elapsed <= self.delay {
+            true => self.speed_up(),
+            false => self.slow_down(),
+        }
+    }
+
+    fn speed_up(&mut self) {
+        self.delay = std::cmp::max(self.delay / 2, self.min);
+    }
+
+    fn slow_down(&mut self) {
+        let doubled = std::cmp::max(self.delay * 2, Duration::from_micros(1));
+        self.delay = std::cmp::min(doubled, self.max);
+    }
+}
+
+/// Result of sending the same message to several endpoints.
+#[derive(Default)]
+pub struct SendReport {
+    /// Endpoints that could not receive the data yet (i.e. handshake in progress).
+    /// The data should be sent to them again later.
+    pub busy: Vec<Endpoint>,
+    /// Endpoints that will never receive the data.
+    pub failed: Vec<(Endpoint, SendStatus)>,
+    /// Time spent sending the data to all the endpoints.
+    pub elapsed: Duration,
+}
+
+pub fn send_all<'a>(
+    network: &NetworkController,
+    endpoints: impl Iterator<Item = &'a Endpoint>,
+    data: &[u8],
+) -> SendReport {
+    let mut report = SendReport::default();
+    let start = Instant::now();
+    for endpoint in endpoints {
+        match network.send(*endpoint, data) {
+            SendStatus::Sent => (),
+            SendStatus::ResourceNotAvailable => report.busy.push(*endpoint),
+            status => report.failed.push((*endpoint, status)),
+        }
+    }
+    report.elapsed = start.elapsed();
+    report
+}
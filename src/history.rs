@@ -0,0 +1,169 @@
+use crate::state::{ChatMessage, MessageType};
+use crate::util::Result;
+
+use chrono::{Local, TimeZone};
+use serde::{Serialize, Deserialize};
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum HistoryKind {
+    Chat,
+    Game,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub timestamp: i64,
+    pub kind: HistoryKind,
+    pub user: String,
+    pub content: String,
+}
+
+impl HistoryEntry {
+    /// Each entry is stored in a single line: `id timestamp kind user content` separated by tabs
+    fn to_line(&self) -> String {
+        let kind = match self.kind {
+            HistoryKind::Chat => "chat",
+            HistoryKind::Game => "game",
+        };
+        format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            self.id,
+            self.timestamp,
+            kind,
+            escape(&self.user),
+            escape(&self.content)
+        )
+    }
+
+    fn from_line(line: &str) -> Option<HistoryEntry> {
+        let mut fields = line.splitn(5, '\t');
+        let id = fields.next()?.parse().ok()?;
+        let timestamp = fields.next()?.parse().ok()?;
+        let kind = match fields.next()? {
+            "chat" => HistoryKind::Chat,
+            "game" => HistoryKind::Game,
+            _ => return None,
+        };
+        let user = unescape(fields.next()?);
+        let content = unescape(fields.next()?);
+        Some(HistoryEntry { id, timestamp, kind, user, content })
+    }
+
+    pub fn to_chat_message(&self) -> ChatMessage {
+        let date = Local.timestamp_opt(self.timestamp, 0).single().unwrap_or_else(Local::now);
+        let message_type = match self.kind {
+            HistoryKind::Chat => MessageType::Text(self.content.clone()),
+            HistoryKind::Game => MessageType::Game(self.content.clone()),
+        };
+        ChatMessage { date, user: self.user.clone(), message_type }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Bounded chat and game history kept by the table in an append-only file.
+pub struct History {
+    path: PathBuf,
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+    lines_in_file: usize,
+    next_id: u64,
+}
+
+impl History {
+    const FILE_NAME: &'static str = "history";
+
+    pub fn load(capacity: usize) -> Result<History> {
+        let dir = crate::config::config_dir().ok_or("Unable to find the config directory")?;
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(Self::FILE_NAME);
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let lines_in_file = content.lines().count();
+        let mut entries = content.lines().filter_map(HistoryEntry::from_line).collect::<VecDeque<_>>();
+        while entries.len() > capacity {
+            entries.pop_front();
+        }
+        let next_id = entries.back().map(|entry| entry.id + 1).unwrap_or(0);
+
+        Ok(History { path, entries, capacity, lines_in_file, next_id })
+    }
+
+    /// Id that the next recorded entry will have
+    pub fn next_id(&self) -> u64 {
+        self.next_id
+    }
+
+    pub fn push(&mut self, kind: HistoryKind, user: &str, content: &str) -> Result<()> {
+        let entry = HistoryEntry {
+            id: self.next_id,
+            timestamp: Local::now().timestamp(),
+            kind,
+            user: user.into(),
+            content: content.into(),
+        };
+        self.next_id += 1;
+
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(entry.to_line().as_bytes())?;
+        self.lines_in_file += 1;
+
+        self.entries.push_back(entry);
+        if self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+
+        // Avoid growing the file forever, only the last entries are kept.
+        if self.lines_in_file > self.capacity * 2 {
+            let content = self.entries.iter().map(|entry| entry.to_line()).collect::<String>();
+            std::fs::write(&self.path, content)?;
+            self.lines_in_file = self.entries.len();
+        }
+        Ok(())
+    }
+
+    /// Returns up to `count` entries older than `before` (in chronological order)
+    /// and the id from where the next older page should be requested.
+    pub fn page(&self, before: u64, count: usize) -> (Vec<HistoryEntry>, u64) {
+        let older = self.entries.iter().filter(|entry| entry.id < before).collect::<Vec<_>>();
+        let page = older[older.len().saturating_sub(count)..]
+            .iter()
+            .map(|entry| (*entry).clone())
+            .collect::<Vec<_>>();
+        let next_before = page.first().map(|entry| entry.id).unwrap_or(before);
+        (page, next_before)
+    }
+}
@@ -8,5 +8,7 @@ mod commands;
 mod ui;
 mod util;
 mod encoder;
+mod flow_control;
+mod history;
 pub mod config;
 mod cardascii;
@@ -42,6 +42,29 @@ fn main() {
                 })*/
                 .help("Tcp port used when communicating with other player instances"),
         )
+        .arg(
+            Arg::with_name("solo")
+                .long("solo")
+                .conflicts_with_all(&["player", "table"])
+                .help("Practice alone without network, the game runs locally"),
+        )
+        .arg(
+            Arg::with_name("daily")
+                .long("daily")
+                .conflicts_with_all(&["player", "table", "seed"])
+                .help("Play the daily puzzle in solo mode, the same hands for everyone each day"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .conflicts_with("player")
+                .validator(|seed| match seed.parse::<u64>() {
+                    Ok(_) => Ok(()),
+                    Err(_) => Err("The seed must be a positive number"),
+                })
+                .help("Seed of the deck of the table (or of solo mode without --table), the same seed deals the same hands"),
+        )
         .arg(
             Arg::with_name("username")
                 .long("username")
@@ -49,6 +72,11 @@ fn main() {
                 .short('u')
                 .help("Name used as user idenfication"),
         )
+        .arg(
+            Arg::with_name("sealed")
+                .long("sealed")
+                .help("Table rule: hide the answers from the other players until the turn ends"),
+        )
         .arg(
             Arg::with_name("quiet-mode")
                 .long("quiet-mode")
@@ -1,6 +1,9 @@
 use rgb::RGB8;
 use serde::{Deserialize, Serialize};
-use crate::cardascii::common::HandCardData;
+use crate::cardascii::common::{Card, HandCardData, TurnRecord, TurnStatus};
+use crate::cardascii::answer::AnswerExplanation;
+use crate::cardascii::rules::Rules;
+use crate::history::HistoryEntry;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Chunk {
@@ -16,7 +19,16 @@ pub enum NetMessage {
     UserMessage(String),                       // content
     UserData(String, Chunk),                   // file_name, chunk
     Stream(Option<(Vec<RGB8>, usize, usize)>), // Option of (stream_data width, height ) None means stream has ended
-    CardasciiAnswer(String),
-    CardasciiNewTurn(HandCardData),
-    CardasciiPass()
+    DirectMessage(String, String, String),     // from_user, to_user, content
+    HistoryRequest(Option<u64>, usize),        // older than this entry id (None means newest), count
+    History(Vec<HistoryEntry>, u64),           // entries, id from where to request older entries
+    // Answers and passes carry the number of the turn they target
+    CardasciiAnswer(u32, String),               // turn, answer
+    CardasciiRules(Rules),                     // rules of the table, sent on join
+    CardasciiNewTurn(TurnStatus, HandCardData, Option<Card>), // turn and deck, hand, target card (Krypto)
+    CardasciiTurnStatus(TurnStatus),           // sent when it changes during the turn
+    CardasciiPass(u32),                        // turn
+    CardasciiTurnResult(TurnRecord),
+    CardasciiEvent(String),                    // game notice from the table
+    CardasciiRejected(String, AnswerExplanation), // user, why the answer is not correct
 }
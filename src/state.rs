@@ -2,8 +2,16 @@ use message_io::network::Endpoint;
 use chrono::{DateTime, Local};
 use rgb::RGB8;
 
+use std::cell::Cell;
 use std::collections::HashMap;
 use crate::cardascii::game::Game24;
+use crate::cardascii::builder::AnswerBuilder;
+use crate::cardascii::bot::Bot;
+use crate::cardascii::solo::Solo;
+use crate::cardascii::common::{Card, TurnRecord, TurnStatus};
+use crate::cardascii::rules::Rules;
+use crate::history::{History, HistoryEntry, HistoryKind};
+use crate::util::Reportable;
 
 #[derive(PartialEq)]
 pub enum SystemMessageType {
@@ -23,6 +31,8 @@ pub enum MessageType {
     Connection,
     Disconnection,
     Text(String),
+    Private(String),
+    Game(String),
     System(String, SystemMessageType),
     Progress(ProgressState),
 }
@@ -37,6 +47,34 @@ impl ChatMessage {
     pub fn new(user: String, message_type: MessageType) -> ChatMessage {
         ChatMessage { date: Local::now(), user, message_type }
     }
+
+    pub fn passes(&self, filter: MessageFilter) -> bool {
+        match filter {
+            MessageFilter::All => true,
+            MessageFilter::Chat => {
+                matches!(self.message_type, MessageType::Text(_) | MessageType::Private(_))
+            }
+            MessageFilter::Game => matches!(self.message_type, MessageType::Game(_)),
+            MessageFilter::System => matches!(
+                self.message_type,
+                MessageType::System(..)
+                    | MessageType::Connection
+                    | MessageType::Disconnection
+                    | MessageType::Progress(_)
+            ),
+        }
+    }
+
+    /// Text where the search is performed
+    pub fn content(&self) -> &str {
+        match &self.message_type {
+            MessageType::Text(content) => content,
+            MessageType::Private(content) => content,
+            MessageType::Game(content) => content,
+            MessageType::System(content, _) => content,
+            _ => "",
+        }
+    }
 }
 
 pub struct Window {
@@ -52,16 +90,43 @@ impl Window {
 #[derive(Default)]
 pub struct State {
     messages: Vec<ChatMessage>,
+    history_messages: Vec<ChatMessage>,
+    oldest_history_id: Option<u64>,
     scroll_messages_view: usize,
+    // After a search the view starts at the selected match, until it is scrolled
+    scroll_from_match: bool,
+    // Line of the selected match in the last drawn panel, measured by the ui as it wraps the messages
+    search_match_line: Cell<usize>,
     input: Vec<char>,
+    message_filter: MessageFilter,
+    search: Option<String>,
+    search_match: usize,
     input_cursor: usize,
     lan_users: HashMap<Endpoint, String>,
+    // Table joined by the player, known when it sends its rules
+    table: Option<Endpoint>,
     users_id: HashMap<String, usize>,
     last_user_id: usize,
+    last_private_user: Option<String>,
+    pub local_user: String,
     pub stop_stream: bool,
     pub windows: HashMap<Endpoint, Window>,
     pub(crate) game24: Option<Game24>,
+    pub(crate) history: Option<History>,
+    // Computer players of the table
+    bots: Vec<Bot>,
+    // Bots removed from the table, their departure is announced by the table
+    left_bots: Vec<String>,
+    // Stats of the offline practice, only in solo mode
+    solo: Option<Solo>,
+    turn_results: Vec<TurnRecord>,
     pub cards : Vec<Vec<String>>,
+    // Turn of the cards received from the table, with the state of the deck
+    turn_status: TurnStatus,
+    hand: Vec<Card>,
+    target_card: Option<Card>,
+    rules: Rules,
+    answer_builder: Option<AnswerBuilder>,
 }
 
 pub enum CursorMovement {
@@ -77,15 +142,261 @@ pub enum ScrollMovement {
     Start,
 }
 
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum MessageFilter {
+    #[default]
+    All,
+    Chat,
+    Game,
+    System,
+}
+
+pub enum SearchMovement {
+    Next,
+    Previous,
+}
+
+
 impl State {
+    /// Input starting with this prefix searches in the messages instead of being sent
+    pub const SEARCH_PREFIX: &'static str = "/";
+
     pub fn messages(&self) -> &Vec<ChatMessage> {
         &self.messages
     }
 
+    /// Messages from previous sessions, older than any message in `messages()`
+    pub fn history_messages(&self) -> &Vec<ChatMessage> {
+        &self.history_messages
+    }
+
+    pub fn oldest_history_id(&self) -> Option<u64> {
+        self.oldest_history_id
+    }
+
+    /// Turn number that the answers and passes target
+    pub fn current_turn(&self) -> u32 {
+        match &self.game24 {
+            Some(game) => game.turn_num(),
+            None => self.turn_status.turn,
+        }
+    }
+
+    pub fn turn_status(&self) -> TurnStatus {
+        match &self.game24 {
+            Some(game) => game.status(),
+            None => self.turn_status,
+        }
+    }
+
+    pub fn set_turn_status(&mut self, status: TurnStatus) {
+        self.turn_status = status;
+    }
+
+    /// Cards of the current turn, in the same order they are drawn in the cards panel
+    pub fn hand(&self) -> Vec<Card> {
+        match &self.game24 {
+            Some(game) => game.get_gived_cards().into_iter().cloned().collect(),
+            None => self.hand.clone(),
+        }
+    }
+
+    /// Card whose value is the target of the turn in the Krypto variant
+    pub fn target_card(&self) -> Option<Card> {
+        match &self.game24 {
+            Some(game) => game.target_card().cloned(),
+            None => self.target_card,
+        }
+    }
+
+    pub fn set_hand(&mut self, status: TurnStatus, hand: Vec<Card>, target_card: Option<Card>) {
+        self.turn_status = status;
+        self.hand = hand;
+        self.target_card = target_card;
+        // The answer being built was for the previous cards
+        if let Some(builder) = &mut self.answer_builder {
+            builder.clear();
+        }
+    }
+
+    /// Rules of the game played in the table
+    pub fn rules(&self) -> &Rules {
+        match &self.game24 {
+            Some(game) => game.rules(),
+            None => &self.rules,
+        }
+    }
+
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+    }
+
+    /// Seed of the deck of the game, to replay it. Only known by the table (or in solo mode)
+    pub fn seed(&self) -> Option<u64> {
+        self.game24.as_ref().map(Game24::seed)
+    }
+
+    pub fn bots(&self) -> &Vec<Bot> {
+        &self.bots
+    }
+
+    pub fn bots_mut(&mut self) -> &mut Vec<Bot> {
+        &mut self.bots
+    }
+
+    pub fn bot_mut(&mut self, name: &str) -> Option<&mut Bot> {
+        self.bots.iter_mut().find(|bot| bot.name == name)
+    }
+
+    /// Removes the bots that match, returns how many were removed
+    pub fn remove_bots(&mut self, remove: impl Fn(&Bot) -> bool) -> usize {
+        let (removed, kept) = std::mem::take(&mut self.bots).into_iter().partition::<Vec<_>, _>(remove);
+        self.bots = kept;
+        self.left_bots.extend(removed.iter().map(|bot| bot.name.clone()));
+        removed.len()
+    }
+
+    pub fn take_left_bots(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.left_bots)
+    }
+
+    pub fn solo(&self) -> Option<&Solo> {
+        self.solo.as_ref()
+    }
+
+    pub fn solo_mut(&mut self) -> Option<&mut Solo> {
+        self.solo.as_mut()
+    }
+
+    pub fn set_solo(&mut self, solo: Option<Solo>) {
+        self.solo = solo;
+    }
+
+    pub fn answer_builder(&self) -> Option<&AnswerBuilder> {
+        self.answer_builder.as_ref()
+    }
+
+    pub fn answer_builder_mut(&mut self) -> Option<&mut AnswerBuilder> {
+        self.answer_builder.as_mut()
+    }
+
+    pub fn toggle_answer_builder(&mut self) {
+        self.answer_builder = match self.answer_builder.take() {
+            Some(_) => None,
+            None => Some(AnswerBuilder::new(&self.rules().operators)),
+        };
+    }
+
+    pub fn turn_results(&self) -> &Vec<TurnRecord> {
+        &self.turn_results
+    }
+
+    pub fn add_turn_result(&mut self, record: TurnRecord) {
+        self.turn_results.push(record);
+    }
+
+    pub fn add_history(&mut self, entries: Vec<HistoryEntry>, oldest_id: u64) {
+        let older = entries.iter().map(|entry| entry.to_chat_message());
+        self.history_messages.splice(0..0, older);
+        self.oldest_history_id = Some(oldest_id);
+    }
+
+    /// Stores the entry in the history if this instance keeps one (only the table does)
+    pub fn record_history(&mut self, kind: HistoryKind, user: &str, content: &str) {
+        if let Some(history) = &mut self.history {
+            history.push(kind, user, content).report_if_err(self);
+        }
+    }
+
     pub fn scroll_messages_view(&self) -> usize {
         self.scroll_messages_view
     }
 
+    /// Whether the messages panel starts at the selected search match
+    pub fn follows_search_match(&self) -> bool {
+        self.scroll_from_match && self.current_search_match().is_some()
+    }
+
+    pub fn set_search_match_line(&self, line: usize) {
+        self.search_match_line.set(line);
+    }
+
+    /// Messages shown in the panel from the newest to the oldest one
+    pub fn visible_messages(&self) -> Vec<&ChatMessage> {
+        self.history_messages
+            .iter()
+            .chain(self.messages.iter())
+            .rev()
+            .filter(|message| message.passes(self.message_filter))
+            .collect()
+    }
+
+    pub fn message_filter(&self) -> MessageFilter {
+        self.message_filter
+    }
+
+    pub fn set_message_filter(&mut self, filter: MessageFilter) {
+        self.message_filter = filter;
+        self.scroll_messages_view = 0;
+        self.scroll_from_match = false;
+        self.search_match = 0;
+    }
+
+    pub fn search(&self) -> Option<&str> {
+        self.search.as_deref()
+    }
+
+    /// Index in `visible_messages()` of the match selected
+    pub fn current_search_match(&self) -> Option<usize> {
+        self.search_matches().get(self.search_match).copied()
+    }
+
+    pub fn search_matches(&self) -> Vec<usize> {
+        match &self.search {
+            Some(query) => self
+                .visible_messages()
+                .iter()
+                .enumerate()
+                .filter(|(_, message)| !crate::util::find_matches(message.content(), query).is_empty())
+                .map(|(index, _)| index)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn set_search(&mut self, query: Option<String>) {
+        self.search = query.filter(|query| !query.is_empty());
+        self.search_match = 0;
+        self.scroll_to_search_match();
+    }
+
+    /// Incremental search: the search is updated while the query is being written
+    pub fn update_search_from_input(&mut self) {
+        let input = self.input.iter().collect::<String>();
+        if let Some(query) = input.strip_prefix(Self::SEARCH_PREFIX) {
+            self.set_search(Some(query.to_string()));
+        }
+    }
+
+    pub fn search_move(&mut self, movement: SearchMovement) {
+        let matches = self.search_matches().len();
+        if matches == 0 {
+            return
+        }
+        self.search_match = match movement {
+            // Next goes to older messages, as the search starts from the newest one
+            SearchMovement::Next => (self.search_match + 1) % matches,
+            SearchMovement::Previous => (self.search_match + matches - 1) % matches,
+        };
+        self.scroll_to_search_match();
+    }
+
+    fn scroll_to_search_match(&mut self) {
+        if self.current_search_match().is_some() {
+            self.scroll_from_match = true;
+        }
+    }
+
     pub fn input(&self) -> &[char] {
         &self.input
     }
@@ -120,6 +431,23 @@ impl State {
         self.lan_users.get(endpoint)
     }
 
+    pub fn user_endpoint(&self, user: &str) -> Option<&Endpoint> {
+        self.lan_users.iter().find(|(_, name)| *name == user).map(|(endpoint, _)| endpoint)
+    }
+
+    /// Endpoint of the table joined, it relays the messages to the players not directly connected
+    pub fn table_endpoint(&self) -> Option<&Endpoint> {
+        self.table.as_ref()
+    }
+
+    pub fn set_table_endpoint(&mut self, endpoint: Endpoint) {
+        self.table = Some(endpoint);
+    }
+
+    pub fn last_private_user(&self) -> Option<&String> {
+        self.last_private_user.as_ref()
+    }
+
     pub fn all_user_endpoints(&self) -> impl Iterator<Item = &Endpoint> {
         self.lan_users.keys()
     }
@@ -144,6 +472,9 @@ impl State {
         if self.lan_users.contains_key(&endpoint) {
             // unwrap is safe because of the check above
             let user = self.lan_users.remove(&endpoint).unwrap();
+            if self.table == Some(endpoint) {
+                self.table = None;
+            }
             self.add_message(ChatMessage::new(user, MessageType::Disconnection));
         }
     }
@@ -188,6 +519,11 @@ impl State {
     }
 
     pub fn messages_scroll(&mut self, movement: ScrollMovement) {
+        // The view is scrolled from where the search left it
+        if self.follows_search_match() {
+            self.scroll_messages_view = self.search_match_line.get();
+        }
+        self.scroll_from_match = false;
         match movement {
             ScrollMovement::Up => {
                 if self.scroll_messages_view > 0 {
@@ -198,7 +534,7 @@ impl State {
                 self.scroll_messages_view += 1;
             }
             ScrollMovement::Start => {
-                self.scroll_messages_view += 0;
+                self.scroll_messages_view = 0;
             }
         }
     }
@@ -215,6 +551,11 @@ impl State {
         self.messages.push(message);
     }
 
+    pub fn add_private_message(&mut self, from: &str, content: String) {
+        self.last_private_user = Some(from.into());
+        self.messages.push(ChatMessage::new(from.into(), MessageType::Private(content)));
+    }
+
     pub fn add_system_warn_message(&mut self, content: String) {
         let message_type = MessageType::System(content, SystemMessageType::Warning);
         let message = ChatMessage::new("Termchat: ".into(), message_type);
@@ -2,9 +2,9 @@ use resize::{Pixel::RGB8, px::RGB};
 use resize::Type::Lanczos3;
 use crate::{config::Theme, state::Window};
 
-use super::state::{ProgressState, State, MessageType, SystemMessageType};
+use super::state::{ProgressState, State, MessageType, MessageFilter, SystemMessageType};
 use super::commands::{CommandManager};
-use super::util::{split_each};
+use super::util::{split_each, find_matches, wrapped_lines};
 
 use tui::backend::CrosstermBackend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
@@ -15,6 +15,13 @@ use tui::Frame;
 
 use std::io::Write;
 use crate::cardascii::terminal::draw_hand_from_vec_cards;
+use crate::cardascii::terminal::draw_single_card;
+use crate::cardascii::terminal::draw_card_back;
+use crate::cardascii::common::Card;
+use crate::cardascii::builder::{AnswerBuilder, Piece};
+use crate::cardascii::answer::format_value;
+use crate::cardascii::terminal::{card_label, draw_mini_card, HAND_COLUMNS, MINI_CARD_WIDTH};
+use crate::cardascii::art::{CARD_HEIGHT, CARD_WIDTH};
 
 pub fn draw(
     frame: &mut Frame<CrosstermBackend<impl Write>>,
@@ -28,23 +35,99 @@ pub fn draw(
         .split(chunk);
 
     let upper_chunk = v_chunks[0];
-    draw_messages_panel(frame, state, v_chunks[0], theme);
-    
-    let upper_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Min(15), Constraint::Length(31)].as_ref())
-        .split(upper_chunk);
-    draw_messages_panel(frame, state, upper_chunks[0], theme);
+
+    let hand = match &state.game24 {
+        // The deck could be empty: there is no hand to show
+        Some(game) if state.hand().len() != game.rules().cards => Vec::new(),
+        _ => state.hand(),
+    };
+    let (messages_chunk, cards_chunk, card_size) = card_panel_layout(
+        upper_chunk,
+        hand.len(),
+        state.target_card().is_some(),
+        state.answer_builder().is_some(),
+    );
+    draw_messages_panel(frame, state, messages_chunk, theme);
     //draw_video_panel(frame, state, upper_chunks[1]);
     if let Some(game) = &state.game24 {
-        draw_card_panel(frame, upper_chunks[1], & draw_hand_from_vec_cards(& game.get_gived_cards()));
+        let visual_cards = match hand.is_empty() {
+            true => Vec::new(),
+            false => draw_hand_from_vec_cards(& game.get_gived_cards()),
+        };
+        draw_card_panel(frame, state, cards_chunk, card_size, & visual_cards, &hand, theme);
     }
     else {
-        draw_card_panel(frame, upper_chunks[1], & state.cards);
+        draw_card_panel(frame, state, cards_chunk, card_size, & state.cards, &hand, theme);
     }
     draw_input_panel(frame, state, v_chunks[1], theme);
 }
 
+/// How the cards are drawn, depending on the room left by the chat
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CardSize {
+    /// The whole art of the cards
+    Full,
+    /// Cards of 3 lines with their value and suit
+    Compact,
+    /// A single line: `[7♣] [12⚔]`
+    Strip,
+}
+
+/// Splits the area between the chat and the cards panel.
+/// The panel goes at the right of the chat with the biggest cards that fit,
+/// or below the chat when the terminal is narrow or portrait-shaped.
+fn card_panel_layout(area: Rect, cards: usize, target: bool, builder: bool) -> (Rect, Rect, CardSize) {
+    const MIN_CHAT_WIDTH: u16 = 30;
+    const FULL_WIDTH: u16 = 2 * CARD_WIDTH as u16 + 3;
+    const COMPACT_WIDTH: u16 = 2 * MINI_CARD_WIDTH as u16 + 3;
+
+    // The back of the cards is shown when there is no hand
+    let rows = cards.max(1).div_ceil(HAND_COLUMNS) as u16;
+    let target = target as u16;
+    let builder = if builder { 5 } else { 0 };
+    let full_height = rows * (CARD_HEIGHT as u16 + 1) + target * CARD_HEIGHT as u16 + 2 + builder;
+    let compact_height = (rows + target) * 3 + 2 + builder;
+
+    let beside = |width: u16, size: CardSize| {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(MIN_CHAT_WIDTH), Constraint::Length(width)].as_ref())
+            .split(area);
+        (chunks[0], chunks[1], size)
+    };
+    let below = |height: u16, size: CardSize| {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)].as_ref())
+            .split(area);
+        (chunks[0], chunks[1], size)
+    };
+
+    // Terminal cells are about twice as high as wide
+    let portrait = area.width < area.height * 2;
+    if portrait {
+        let room = area.height / 2;
+        if area.width >= FULL_WIDTH && full_height <= room {
+            below(full_height, CardSize::Full)
+        }
+        else if area.width >= COMPACT_WIDTH && compact_height <= room {
+            below(compact_height, CardSize::Compact)
+        }
+        else {
+            below(3 + builder, CardSize::Strip)
+        }
+    }
+    else if area.width >= FULL_WIDTH + MIN_CHAT_WIDTH && full_height <= area.height {
+        beside(FULL_WIDTH, CardSize::Full)
+    }
+    else if area.width >= COMPACT_WIDTH + MIN_CHAT_WIDTH && compact_height <= area.height {
+        beside(COMPACT_WIDTH, CardSize::Compact)
+    }
+    else {
+        below(3 + builder, CardSize::Strip)
+    }
+}
+
 fn draw_messages_panel(
     frame: &mut Frame<CrosstermBackend<impl Write>>,
     state: &State,
@@ -53,11 +136,18 @@ fn draw_messages_panel(
 ) {
     let message_colors = &theme.message_colors;
 
+    let search = state.search();
+    let current_match = state.current_search_match();
+
     let messages = state
-        .messages()
-        .iter()
-        .rev()
-        .map(|message| {
+        .visible_messages()
+        .into_iter()
+        .enumerate()
+        .map(|(index, message)| {
+            let highlight = match current_match == Some(index) {
+                true => Style::default().bg(theme.search_color).add_modifier(Modifier::BOLD),
+                false => Style::default().add_modifier(Modifier::REVERSED),
+            };
             let color = if let Some(id) = state.users_id().get(&message.user) {
                 message_colors[id % message_colors.len()]
             } else {
@@ -81,7 +171,35 @@ fn draw_messages_panel(
                         Span::styled(&message.user, Style::default().fg(color)),
                         Span::styled(": ", Style::default().fg(color)),
                     ];
-                    ui_message.extend(parse_content(content, theme));
+                    let content = parse_content(content, theme);
+                    match search {
+                        Some(query) => ui_message.extend(highlight_spans(content, query, highlight)),
+                        None => ui_message.extend(content),
+                    }
+                    Spans::from(ui_message)
+                }
+                MessageType::Game(content) => {
+                    let mut ui_message = vec![
+                        Span::styled(date, Style::default().fg(theme.date_color)),
+                        Span::styled(&message.user, Style::default().fg(color)),
+                        Span::styled(": ", Style::default().fg(color)),
+                    ];
+                    let content_style = Style::default().fg(theme.game_event_color);
+                    let query = search.unwrap_or("");
+                    ui_message.extend(highlight_content(content, query, content_style, highlight));
+                    Spans::from(ui_message)
+                }
+                MessageType::Private(content) => {
+                    let private_style = Style::default().fg(color).add_modifier(Modifier::ITALIC);
+                    let mut ui_message = vec![
+                        Span::styled(date, Style::default().fg(theme.date_color)),
+                        Span::styled(&message.user, Style::default().fg(color)),
+                        Span::styled(" (private)", private_style),
+                        Span::styled(": ", Style::default().fg(color)),
+                    ];
+                    let content_style = Style::default().add_modifier(Modifier::ITALIC);
+                    let query = search.unwrap_or("");
+                    ui_message.extend(highlight_content(content, query, content_style, highlight));
                     Spans::from(ui_message)
                 }
                 MessageType::System(content, msg_type) => {
@@ -90,11 +208,14 @@ fn draw_messages_panel(
                         SystemMessageType::Warning => theme.system_warning_color,
                         SystemMessageType::Error => theme.system_error_color,
                     };
-                    Spans::from(vec![
+                    let mut ui_message = vec![
                         Span::styled(date, Style::default().fg(theme.date_color)),
                         Span::styled(&message.user, Style::default().fg(user_color)),
-                        Span::styled(content, Style::default().fg(content_color)),
-                    ])
+                    ];
+                    let content_style = Style::default().fg(content_color);
+                    let query = search.unwrap_or("");
+                    ui_message.extend(highlight_content(content, query, content_style, highlight));
+                    Spans::from(ui_message)
                 }
                 MessageType::Progress(state) => {
                     Spans::from(add_progress_bar(chunk.width, state, theme))
@@ -103,20 +224,102 @@ fn draw_messages_panel(
         })
         .collect::<Vec<_>>();
 
+    // The panel wraps the messages, so the selected match starts after all the lines above it
+    let scroll = match current_match {
+        Some(index) if state.follows_search_match() => {
+            let width = chunk.width.saturating_sub(2) as usize;
+            let line = messages
+                .iter()
+                .take(index)
+                .map(|spans| {
+                    let text = spans.0.iter().map(|span| span.content.as_ref()).collect::<String>();
+                    wrapped_lines(&text, width)
+                })
+                .sum();
+            state.set_search_match_line(line);
+            line
+        }
+        _ => state.scroll_messages_view(),
+    };
+
     let messages_panel = Paragraph::new(messages)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(Span::styled("LAN Room", Style::default().add_modifier(Modifier::BOLD))),
+                .title(Span::styled(messages_title(state), Style::default().add_modifier(Modifier::BOLD))),
         )
         .style(Style::default().fg(theme.chat_panel_color))
         .alignment(Alignment::Left)
-        .scroll((state.scroll_messages_view() as u16, 0))
+        .scroll((scroll as u16, 0))
         .wrap(Wrap { trim: false });
 
     frame.render_widget(messages_panel, chunk);
 }
 
+fn messages_title(state: &State) -> String {
+    let mut title = String::from("LAN Room");
+    match state.message_filter() {
+        MessageFilter::All => (),
+        MessageFilter::Chat => title.push_str(" [chat]"),
+        MessageFilter::Game => title.push_str(" [game]"),
+        MessageFilter::System => title.push_str(" [system]"),
+    }
+    if let Some(query) = state.search() {
+        let matches = state.search_matches();
+        let current = match state.current_search_match() {
+            Some(index) => matches.iter().position(|m| *m == index).unwrap_or(0) + 1,
+            None => 0,
+        };
+        title.push_str(&format!(" {}{} ({}/{})", State::SEARCH_PREFIX, query, current, matches.len()));
+    }
+    title
+}
+
+/// Styles the occurrences of the query in the spans with `highlight`, keeping the style of the rest
+fn highlight_spans<'a>(spans: Vec<Span<'a>>, query: &str, highlight: Style) -> Vec<Span<'a>> {
+    let text = spans.iter().map(|span| span.content.as_ref()).collect::<String>();
+    let matches = find_matches(&text, query);
+    if matches.is_empty() {
+        return spans
+    }
+    let mut highlighted = Vec::new();
+    let mut start = 0;
+    for span in spans {
+        let end = start + span.content.len();
+        // The span is cut where the matches inside it start and end
+        let mut cuts = vec![start, end];
+        for (match_start, match_end) in &matches {
+            cuts.extend([*match_start, *match_end].iter().filter(|cut| **cut > start && **cut < end));
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+        for cut in cuts.windows(2) {
+            let inside = matches.iter().any(|(match_start, match_end)| *match_start <= cut[0] && cut[1] <= *match_end);
+            let style = if inside { highlight } else { span.style };
+            highlighted.push(Span::styled(text[cut[0]..cut[1]].to_string(), style));
+        }
+        start = end;
+    }
+    highlighted
+}
+
+/// Splits the content in spans, styling the occurrences of the query with `highlight`
+fn highlight_content<'a>(content: &'a str, query: &str, style: Style, highlight: Style) -> Vec<Span<'a>> {
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for (start, end) in find_matches(content, query) {
+        if start > last {
+            spans.push(Span::styled(&content[last..start], style));
+        }
+        spans.push(Span::styled(&content[start..end], highlight));
+        last = end;
+    }
+    if last < content.len() || spans.is_empty() {
+        spans.push(Span::styled(&content[last..], style));
+    }
+    spans
+}
+
 fn add_progress_bar<'a>(
     panel_width: u16,
     progress: &'a ProgressState,
@@ -201,23 +404,188 @@ fn draw_input_panel(
     frame.render_widget(fb, chunk);
 }*/
 
-fn draw_card_panel(frame: &mut Frame<CrosstermBackend<impl Write>>, chunk: Rect, visual_cards: &Vec<Vec<String>>) {
-    let rows = visual_cards.iter().map(|v_card| {
-        let height = v_card
-            .iter()
-            .map(|content| content.chars().filter(|c| *c == '\n').count())
-            .max()
-            .unwrap_or(1);
+fn draw_card_panel(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    state: &State,
+    chunk: Rect,
+    size: CardSize,
+    visual_cards: &[Vec<String>],
+    hand: &[Card],
+    theme: &Theme,
+) {
+    let builder = state.answer_builder();
+    let target = state.target_card();
+    let (cards_chunk, builder_chunk) = match builder {
+        Some(_) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(5)].as_ref())
+                .split(chunk);
+            (chunks[0], Some(chunks[1]))
+        }
+        None => (chunk, None),
+    };
+
+    let selected = builder.map(|builder| builder.selected(hand.len()));
+    let card_style = |card: usize| match builder {
+        Some(_) if selected == Some(Piece::Card(card)) => Style::default()
+            .fg(theme.card_selection_color)
+            .add_modifier(Modifier::BOLD),
+        Some(builder) if builder.is_used(card) => Style::default().fg(Color::DarkGray),
+        _ => match hand.get(card) {
+            Some(card) => Style::default().fg(theme.suit_color(card._type)),
+            None => Style::default(),
+        },
+    };
+    let target_style = Style::default().fg(theme.game_event_color).add_modifier(Modifier::BOLD);
+    let status = state.turn_status();
+    // The status goes in the title and the footer, or only in the title of the strip
+    let title = match (status.turn, size) {
+        (0, _) => String::from("Cards"),
+        (_, CardSize::Strip) => status.to_string(),
+        (turn, _) => format!("Turn {} • {} left in deck", turn, status.deck_left),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    match size {
+        CardSize::Strip => {
+            let mut spans = hand
+                .iter()
+                .enumerate()
+                .map(|(index, card)| Span::styled(format!("[{}] ", card_label(card)), card_style(index)))
+                .collect::<Vec<_>>();
+            if hand.is_empty() {
+                spans.push(Span::raw("waiting for the cards"));
+            }
+            if let Some(target) = &target {
+                spans.push(Span::styled(format!(" target [{}]", card_label(target)), target_style));
+            }
+            let paragraph = Paragraph::new(Spans::from(spans)).block(block);
+            frame.render_widget(paragraph, cards_chunk);
+        }
+        CardSize::Full | CardSize::Compact => {
+            let (cards, target, width) = match size {
+                CardSize::Full => {
+                    // Nothing dealt yet (or the deck is empty): the back of the cards is shown
+                    let cards = match visual_cards.is_empty() {
+                        true => vec![vec![draw_card_back()]],
+                        false => visual_cards.to_vec(),
+                    };
+                    (cards, target.as_ref().map(draw_single_card), CARD_WIDTH)
+                }
+                _ => {
+                    let cards = match hand.is_empty() {
+                        true => vec![vec![draw_mini_card(None)]],
+                        false => hand
+                            .chunks(HAND_COLUMNS)
+                            .map(|row| row.iter().map(|card| draw_mini_card(Some(card))).collect())
+                            .collect(),
+                    };
+                    (cards, target.as_ref().map(|card| draw_mini_card(Some(card))), MINI_CARD_WIDTH)
+                }
+            };
+            let margin = (size == CardSize::Full) as u16;
+            let lines = |content: &String| content.chars().filter(|c| *c == '\n').count() as u16;
+
+            // The deck pile is shown below the cards when there is room for it
+            let pile = match size {
+                CardSize::Full => draw_card_back(),
+                _ => draw_mini_card(None),
+            };
+            let used_height = cards
+                .iter()
+                .map(|row| row.iter().map(lines).max().unwrap_or(1) + margin)
+                .sum::<u16>()
+                + target.as_ref().map(lines).unwrap_or(0)
+                + margin;
+            let pile_row = match !hand.is_empty()
+                && status.deck_left > 0
+                && used_height + lines(&pile) + 2 <= cards_chunk.height
+            {
+                true => {
+                    let height = lines(&pile);
+                    let label = format!("{}{} left\nin deck", "\n".repeat(height as usize / 2), status.deck_left);
+                    Some(Row::new(vec![Cell::from(pile), Cell::from(label)]).height(height))
+                }
+                false => None,
+            };
+
+            let rows = cards.iter().enumerate().map(|(row, v_card)| {
+                let height = v_card.iter().map(lines).max().unwrap_or(1);
+
+                let cells = v_card.iter().enumerate().map(|(column, string)| {
+                    Cell::from(string.clone()).style(card_style(row * HAND_COLUMNS + column))
+                });
+
+                Row::new(cells).height(height).bottom_margin(margin)
+            });
+
+            // Krypto variant: the target card goes below the hand, highlighted
+            let target_row = target.map(|target| {
+                let height = target.chars().filter(|c| *c == '\n').count();
+                let label = format!("{}TARGET", "\n".repeat(height / 2));
+                Row::new(vec![Cell::from(target).style(target_style), Cell::from(label).style(target_style)])
+                    .height(height as u16)
+                    .bottom_margin(margin)
+            });
+            let rows = rows.chain(target_row).chain(pile_row);
+
+            let widths = [Constraint::Length(width as u16), Constraint::Length(width as u16)];
+            let t = Table::new(rows).block(block).widths(&widths);
+            frame.render_widget(t, cards_chunk);
+        }
+    }
+
+    // Footer over the bottom border of the panel
+    if status.turn > 0 && size != CardSize::Strip && cards_chunk.height > 2 && cards_chunk.width > 2 {
+        let footer = format!("pot {} cards • {} passed", status.pot, status.passed);
+        let footer_chunk = Rect::new(
+            cards_chunk.x + 1,
+            cards_chunk.y + cards_chunk.height - 1,
+            cards_chunk.width - 2,
+            1,
+        );
+        frame.render_widget(Paragraph::new(footer), footer_chunk);
+    }
+
+    if let (Some(builder), Some(chunk)) = (builder, builder_chunk) {
+        draw_answer_builder(frame, builder, hand, chunk, theme);
+    }
+}
 
-        let cells = v_card.iter().map(|string| Cell::from(string.as_str()));
+fn draw_answer_builder(
+    frame: &mut Frame<CrosstermBackend<impl Write>>,
+    builder: &AnswerBuilder,
+    hand: &[Card],
+    chunk: Rect,
+    theme: &Theme,
+) {
+    let selected = builder.selected(hand.len());
+    let palette = builder
+        .operators()
+        .iter()
+        .map(|op| {
+            let style = match selected == Piece::Operator(*op) {
+                true => Style::default().fg(theme.card_selection_color).add_modifier(Modifier::BOLD),
+                false => Style::default(),
+            };
+            Span::styled(format!(" {op} "), style)
+        })
+        .collect::<Vec<_>>();
 
-        Row::new(cells).height(height as u16).bottom_margin(1)
-    });
+    let value = match builder.running_value(hand) {
+        Some(value) => format!(" = {}", format_value(value)),
+        None => String::new(),
+    };
+    let expression = Spans::from(vec![
+        Span::raw(builder.expression(hand)),
+        Span::styled(value, Style::default().fg(Color::DarkGray)),
+    ]);
 
-    let t = Table::new(rows)
-        .block(Block::default().borders(Borders::ALL).title("Cards"))
-        .widths(&[Constraint::Length(14), Constraint::Length(14)]);
-    frame.render_widget(t, chunk);
+    let panel = Paragraph::new(vec![Spans::from(palette), expression])
+        .block(Block::default().borders(Borders::ALL).title("Answer (Tab to close)"))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(panel, chunk);
 }
 
 #[derive(Default)]
@@ -22,6 +22,34 @@ pub fn split_each(input: String, width: usize) -> Vec<String> {
     splitted
 }
 
+/// Lines that the text takes when its words are wrapped at `width`, like the messages panel does
+pub fn wrapped_lines(text: &str, width: usize) -> usize {
+    if width == 0 {
+        return 1
+    }
+    let mut lines = 1;
+    let mut line_width = 0;
+    for (index, word) in text.split(' ').enumerate() {
+        let word_width = word.width();
+        // The space before the word is dropped when the word starts a new line
+        let space = usize::from(index > 0 && line_width > 0);
+        if line_width > 0 && line_width + space + word_width > width {
+            lines += 1;
+            line_width = 0;
+        }
+        else {
+            line_width += space;
+        }
+        line_width += word_width;
+        // A word longer than the line is cut
+        while line_width > width {
+            lines += 1;
+            line_width -= width;
+        }
+    }
+    lines
+}
+
 // Errors
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Result<T> = std::result::Result<T, Error>;
@@ -41,6 +69,28 @@ pub fn stringify_sendall_errors(e: Vec<(message_io::network::Endpoint, std::io::
     out
 }
 
+pub fn stringify_send_status_errors(
+    e: Vec<(message_io::network::Endpoint, message_io::network::SendStatus)>,
+) -> String {
+    let mut out = String::new();
+    for (endpoint, status) in e {
+        let reason = match status {
+            message_io::network::SendStatus::MaxPacketSizeExceeded => "message too big",
+            message_io::network::SendStatus::ResourceNotFound => "endpoint disconnected",
+            message_io::network::SendStatus::ResourceNotAvailable => "endpoint not ready",
+            message_io::network::SendStatus::Sent => "sent",
+        };
+        let msg = format!("Failed to send to {}, error: {}", endpoint, reason);
+        out.push_str(&msg);
+        out.push('\n');
+    }
+    // remove last new line
+    if !out.is_empty() {
+        out.pop();
+    }
+    out
+}
+
 use crate::state::State;
 /// Trait for reporting Recoverable errors/ Infos to the user
 pub trait Reportable: Sized {
@@ -74,6 +124,14 @@ impl Reportable for std::result::Result<(), Vec<(message_io::network::Endpoint,
     }
 }
 
+impl Reportable for Vec<(message_io::network::Endpoint, message_io::network::SendStatus)> {
+    fn report_if_err(self, state: &mut State) {
+        if !self.is_empty() {
+            state.add_system_error_message(crate::util::stringify_send_status_errors(self));
+        }
+    }
+}
+
 impl Reportable for Box<dyn std::error::Error + Send + Sync> {
     fn report_err(self, state: &mut State) {
         self.to_string().report_err(state);
@@ -93,3 +151,35 @@ impl Reportable for String {
         state.add_system_warn_message(self);
     }
 }
+
+/// Byte ranges of the case insensitive occurrences of `query` in `text`
+pub fn find_matches(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let query = query.chars().flat_map(char::to_lowercase).collect::<Vec<_>>();
+    let mut matches = Vec::new();
+    if query.is_empty() {
+        return matches
+    }
+
+    let mut start_indices = text.char_indices().map(|(index, _)| index).peekable();
+    while let Some(start) = start_indices.next() {
+        let mut candidate = text[start..].char_indices().flat_map(|(index, c)| {
+            c.to_lowercase().map(move |lower| (index + c.len_utf8(), lower))
+        });
+        let mut end = start;
+        let found = query.iter().all(|q| match candidate.next() {
+            Some((next_end, c)) if c == *q => {
+                end = start + next_end;
+                true
+            }
+            _ => false,
+        });
+        if found {
+            matches.push((start, end));
+            // Do not report overlapped matches
+            while start_indices.peek().map(|index| *index < end).unwrap_or(false) {
+                start_indices.next();
+            }
+        }
+    }
+    matches
+}